@@ -1,22 +1,231 @@
+use crate::bindings::{Bindings, BindingsSettings};
+use crate::cli::OpenArgs;
+use crate::config::{
+    assign_ids, bounding_boxes, is_yolo_path, next_free_config_path, read_config, write_config,
+    write_json_config, write_legacy_config, ConfigImage, JsonConfig,
+};
 use crate::config_data::ConfigData;
+use crate::crop::CropSettings;
+use crate::dir_scan::{DirScan, ScanSettings};
+use crate::export::ExportSettings;
 use crate::image_data::ImageData;
-use std::path::PathBuf;
+use crate::image_loader::ImageLoader;
+use crate::image_settings::ImageSettings;
+use crate::labels::LabelsWindow;
+use crate::notifications::Notifications;
+use crate::orientation::{orient_config, read_orientation, unorient_config, RoiFrame};
+use crate::pairing::{ConfigImages, PairedConfigs};
+use crate::project::ProjectWindow;
+use crate::roi_inspector::{NewAttribute, Units};
+use crate::session::DEFAULT_UI_SCALE;
+use crate::unsaved_changes::PendingAction;
+use std::path::{Path, PathBuf};
 
 #[derive(Default)]
 pub struct RoIApp {
     pub imgs_paths: Vec<PathBuf>,
     pub selected_img: Option<PathBuf>,
     pub configs_paths: Vec<PathBuf>,
+    /// Bumped whenever `configs_paths` or the image a listed config belongs to changes.
+    pub configs_generation: u64,
     pub selected_config: Option<PathBuf>,
+    /// List every config instead of only those of the selected image.
+    pub show_all_configs: bool,
+    pub paired_configs: PairedConfigs,
+    pub config_images: ConfigImages,
 
     pub img_data: Option<ImageData>,
+    /// Plot bounds of the last session, applied once its image is shown.
+    pub restore_bounds: Option<[f64; 4]>,
+    pub image_loader: ImageLoader,
     pub config_data: ConfigData,
+
+    pub pending_action: Option<PendingAction>,
+    pub export_settings: ExportSettings,
+    pub crop_settings: CropSettings,
+    pub dir_scans: Vec<DirScan>,
+    pub scan_settings: ScanSettings,
+    pub scan_settings_open: bool,
+    pub help_open: bool,
+    pub ui_scale: f32,
+    pub bindings: Bindings,
+    pub bindings_settings: BindingsSettings,
+    pub image_settings: ImageSettings,
+    /// Project file the lists were opened from or saved to.
+    pub project_path: Option<PathBuf>,
+    /// Roi names of the project.
+    pub labels: Vec<String>,
+    pub labels_window: LabelsWindow,
+    pub project_window: ProjectWindow,
+    pub notifications: Notifications,
+    /// Units of the roi inspector.
+    pub roi_units: Units,
+    pub new_attribute: NewAttribute,
 }
 impl RoIApp {
     /// Called once before the first frame.
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        cc.egui_ctx.set_pixels_per_point(1.2);
+    pub fn new(cc: &eframe::CreationContext<'_>, open: OpenArgs) -> Self {
+        let mut app = Self {
+            ui_scale: DEFAULT_UI_SCALE,
+            ..Default::default()
+        };
+        if let Some(storage) = cc.storage {
+            app.restore_state(&cc.egui_ctx, storage);
+        }
+        cc.egui_ctx.set_pixels_per_point(app.ui_scale);
 
-        Default::default()
+        let absolute = |path: PathBuf| std::path::absolute(&path).unwrap_or(path);
+        for path in open.paths.into_iter().map(absolute) {
+            app.add_path(&path);
+        }
+        if let Some(img_path) = open.image.map(absolute) {
+            app.add_path(&img_path);
+            app.open_image(&cc.egui_ctx, img_path);
+        }
+        if let Some(config_path) = open.config.map(absolute) {
+            if !app.configs_paths.contains(&config_path) {
+                app.list_config(config_path.to_path_buf());
+            }
+            app.open_config(config_path);
+        }
+        app
+    }
+    /// Switches to the first config of the selected image, unless the open one already belongs to it.
+    pub fn open_paired_config(&mut self) {
+        let Some(img_path) = &self.selected_img else {
+            return;
+        };
+        if let Some(config_path) = &self.selected_config {
+            if self.config_images.belongs(config_path, img_path) {
+                return;
+            }
+        }
+        let first = self.paired_configs().first().cloned();
+        let action = match first {
+            Some(first) => PendingAction::SwitchConfig(first),
+            None if self.selected_config.is_some() => PendingAction::CloseConfig,
+            None => return,
+        };
+        self.request_action(action);
+    }
+    /// Selects the config and loads it from disk, dropping any unsaved changes.
+    /// A config that doesn't exist yet is opened empty, one that fails to load stays closed.
+    pub fn open_config(&mut self, path: PathBuf) {
+        if !path.exists() {
+            self.config_data.reset_config(Vec::new());
+        } else {
+            match read_config(&path) {
+                Ok(config) => {
+                    let config = match (&self.selected_img, self.image_settings.roi_frame) {
+                        (Some(img_path), RoiFrame::Raw) => {
+                            orient_config(&config, read_orientation(img_path))
+                        }
+                        _ => config,
+                    };
+                    self.config_data.reset_config(config);
+                }
+                Err(err) => {
+                    self.notifications.error(format!("{:#}", err));
+                    return;
+                }
+            }
+        }
+        self.selected_config = Some(path);
+    }
+    /// Rois of the open config in the frame its file uses.
+    pub fn config_in_file_frame(&self) -> Vec<JsonConfig> {
+        match (&self.selected_img, self.image_settings.roi_frame) {
+            (Some(img_path), RoiFrame::Raw) => {
+                unorient_config(&self.config_data.config, read_orientation(img_path))
+            }
+            _ => self.config_data.config.clone(),
+        }
+    }
+    /// Writes the current config to the selected config path. Returns `true` on success.
+    pub fn save_current_config(&mut self) -> bool {
+        let Some(path) = &self.selected_config else {
+            return false;
+        };
+        let config = self.config_in_file_frame();
+        // an unrelated selected image must not be recorded, the config keeps its own
+        let config_images = &mut self.config_images;
+        let img_path = self
+            .selected_img
+            .as_deref()
+            .filter(|img_path| config_images.belongs(path, img_path));
+        let written = if self.image_settings.legacy_configs {
+            write_legacy_config(path, &config)
+        } else if is_yolo_path(path) {
+            // YOLO has no angle, rotated rois are saved as their bounding boxes
+            match img_path {
+                Some(img_path) => bounding_boxes(&config, img_path, self.image_settings.roi_frame)
+                    .and_then(|config| write_config(path, &config, None)),
+                None => write_config(path, &config, None),
+            }
+        } else {
+            // hashed while decoding the image, nothing to record until it's shown
+            let fingerprint = self
+                .img_data
+                .as_ref()
+                .filter(|_| !self.image_loader.is_loading())
+                .map(|img_data| img_data.fingerprint.clone());
+            let config_dir = path.parent().unwrap_or(Path::new(""));
+            let image = img_path
+                .zip(fingerprint)
+                .map(|(img_path, fingerprint)| ConfigImage::new(img_path, fingerprint, config_dir));
+            write_json_config(path, &config, image)
+        };
+        match written {
+            Ok(()) => {
+                self.config_images.forget(path);
+                self.configs_generation += 1;
+                self.notifications.info(format!("Saved {}", path.display()));
+                self.config_data.mark_saved();
+                true
+            }
+            Err(err) => {
+                self.notifications
+                    .error(format!("Saving failed: {:#}", err));
+                false
+            }
+        }
+    }
+    /// Deselects the config and clears its rois.
+    pub fn close_config(&mut self) {
+        self.selected_config = None;
+        self.config_data.reset_config(Vec::new());
+    }
+    /// Appends the config to the list.
+    pub fn list_config(&mut self, path: PathBuf) {
+        self.configs_paths.push(path);
+        self.configs_generation += 1;
+    }
+    /// Removes the config from the list, closing it if it was selected.
+    pub fn remove_config(&mut self, path: &PathBuf) {
+        self.configs_paths.retain(|p| p != path);
+        self.config_images.forget(path);
+        self.configs_generation += 1;
+        if self.selected_config.as_ref() == Some(path) {
+            self.close_config();
+        }
+    }
+    /// Writes imported rois as new configs next to their images and lists both.
+    pub fn add_imported(&mut self, items: Vec<(PathBuf, Vec<JsonConfig>)>) -> anyhow::Result<()> {
+        for (img_path, mut config) in items {
+            if !self.imgs_paths.contains(&img_path) {
+                self.imgs_paths.push(img_path.to_path_buf());
+            }
+            if config.is_empty() {
+                continue;
+            }
+            let Some(config_path) = next_free_config_path(&img_path, &self.configs_paths) else {
+                continue;
+            };
+            assign_ids(&mut config);
+            write_config(&config_path, &config, Some(&img_path))?;
+            self.config_images.forget(&config_path);
+            self.list_config(config_path);
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,377 @@
+use crate::app::RoIApp;
+use crate::shortcuts::Action;
+use egui::{
+    ComboBox, Event, Grid, Key, KeyboardShortcut, ModifierNames, Modifiers, PointerButton,
+    Response, Window,
+};
+use serde::{Deserialize, Serialize};
+
+/// What the mouse does on the image.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum MouseAction {
+    Pan,
+    Select,
+    Edit,
+    Draw,
+    Delete,
+}
+impl MouseAction {
+    pub const ALL: [Self; 5] = [
+        Self::Pan,
+        Self::Select,
+        Self::Edit,
+        Self::Draw,
+        Self::Delete,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Pan => "drag to move the image",
+            Self::Select => "click to select a bbox",
+            Self::Edit => "drag to move edges, corners or the whole selected bbox",
+            Self::Draw => "drag in draw mode to draw a bbox",
+            Self::Delete => "click to remove images, configs and bboxes",
+        }
+    }
+}
+
+const BUTTONS: [PointerButton; 5] = [
+    PointerButton::Primary,
+    PointerButton::Secondary,
+    PointerButton::Middle,
+    PointerButton::Extra1,
+    PointerButton::Extra2,
+];
+fn button_name(button: PointerButton) -> &'static str {
+    match button {
+        PointerButton::Primary => "Left",
+        PointerButton::Secondary => "Right",
+        PointerButton::Middle => "Middle",
+        PointerButton::Extra1 => "Back",
+        PointerButton::Extra2 => "Forward",
+    }
+}
+
+/// Mouse button together with the modifiers that have to be held.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct MouseBinding {
+    pub button: PointerButton,
+    pub modifiers: Modifiers,
+}
+impl MouseBinding {
+    const fn new(button: PointerButton) -> Self {
+        Self {
+            button,
+            modifiers: Modifiers::NONE,
+        }
+    }
+    fn modifiers_held(&self, response: &Response) -> bool {
+        response
+            .ctx
+            .input(|i| i.modifiers.matches_exact(self.modifiers))
+    }
+    pub fn clicked(&self, response: &Response) -> bool {
+        response.clicked_by(self.button) && self.modifiers_held(response)
+    }
+    pub fn drag_started(&self, response: &Response) -> bool {
+        response.drag_started_by(self.button) && self.modifiers_held(response)
+    }
+    /// Modifiers only matter when the drag starts.
+    pub fn dragged(&self, response: &Response) -> bool {
+        response.dragged_by(self.button)
+    }
+    pub fn drag_stopped(&self, response: &Response) -> bool {
+        response.drag_stopped_by(self.button)
+    }
+    pub fn format(&self, ctx: &egui::Context) -> String {
+        let is_mac = matches!(ctx.os(), egui::os::OperatingSystem::Mac);
+        let modifiers = ModifierNames::NAMES.format(&self.modifiers, is_mac);
+        let button = format!("{}Click", button_name(self.button));
+        if modifiers.is_empty() {
+            button
+        } else {
+            format!("{}+{}", modifiers, button)
+        }
+    }
+}
+
+/// Keyboard shortcuts and mouse buttons of every action, persisted between sessions.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Bindings {
+    /// An action may have several shortcuts.
+    pub keys: Vec<(Action, KeyboardShortcut)>,
+    pub mouse: Vec<(MouseAction, MouseBinding)>,
+}
+impl Default for Bindings {
+    fn default() -> Self {
+        let key = KeyboardShortcut::new;
+        Self {
+            keys: vec![
+                (
+                    Action::Redo,
+                    key(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::Z),
+                ),
+                (Action::Undo, key(Modifiers::COMMAND, Key::Z)),
+                (Action::Save, key(Modifiers::COMMAND, Key::S)),
+                (Action::NextConfig, key(Modifiers::SHIFT, Key::D)),
+                (Action::PrevConfig, key(Modifiers::SHIFT, Key::A)),
+                (Action::NextImage, key(Modifiers::NONE, Key::D)),
+                (Action::PrevImage, key(Modifiers::NONE, Key::A)),
+                (Action::NextRoi, key(Modifiers::NONE, Key::E)),
+                (Action::PrevRoi, key(Modifiers::NONE, Key::Q)),
+                (Action::DeleteRoi, key(Modifiers::NONE, Key::Delete)),
+                (Action::DeleteRoi, key(Modifiers::NONE, Key::Backspace)),
+                (Action::ToggleDraw, key(Modifiers::NONE, Key::N)),
+                (Action::CancelEdit, key(Modifiers::NONE, Key::Escape)),
+                (Action::ToggleHelp, key(Modifiers::NONE, Key::F1)),
+            ],
+            mouse: vec![
+                (MouseAction::Pan, MouseBinding::new(PointerButton::Primary)),
+                (
+                    MouseAction::Select,
+                    MouseBinding::new(PointerButton::Secondary),
+                ),
+                (
+                    MouseAction::Edit,
+                    MouseBinding::new(PointerButton::Secondary),
+                ),
+                (MouseAction::Draw, MouseBinding::new(PointerButton::Primary)),
+                (
+                    MouseAction::Delete,
+                    MouseBinding::new(PointerButton::Middle),
+                ),
+            ],
+        }
+    }
+}
+impl Bindings {
+    pub fn mouse(&self, action: MouseAction) -> MouseBinding {
+        self.mouse
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map(|(_, binding)| *binding)
+            .unwrap_or_else(|| {
+                Self::default()
+                    .mouse
+                    .into_iter()
+                    .find(|(bound, _)| *bound == action)
+                    .map(|(_, binding)| binding)
+                    .expect("every mouse action has a default binding")
+            })
+    }
+    fn mouse_mut(&mut self, action: MouseAction) -> &mut MouseBinding {
+        if !self.mouse.iter().any(|(bound, _)| *bound == action) {
+            let binding = self.mouse(action);
+            self.mouse.push((action, binding));
+        }
+        self.mouse
+            .iter_mut()
+            .find(|(bound, _)| *bound == action)
+            .map(|(_, binding)| binding)
+            .expect("just added")
+    }
+    /// Shortcuts ordered so that ones with more modifiers are checked first,
+    /// as `consume_shortcut` ignores extra shift.
+    pub fn ordered_keys(&self) -> Vec<(Action, KeyboardShortcut)> {
+        let mut keys = self.keys.clone();
+        keys.sort_by_key(|(_, shortcut)| {
+            let modifiers = shortcut.modifiers;
+            std::cmp::Reverse(
+                [
+                    modifiers.alt,
+                    modifiers.shift,
+                    modifiers.command || modifiers.ctrl,
+                ]
+                .into_iter()
+                .filter(|held| *held)
+                .count(),
+            )
+        });
+        keys
+    }
+    /// Shortcuts of the action joined with " / ", or "unbound".
+    pub fn format_keys(&self, ctx: &egui::Context, action: Action) -> String {
+        let formatted: Vec<String> = self
+            .keys
+            .iter()
+            .filter(|(bound, _)| *bound == action)
+            .map(|(_, shortcut)| ctx.format_shortcut(shortcut))
+            .collect();
+        if formatted.is_empty() {
+            String::from("unbound")
+        } else {
+            formatted.join(" / ")
+        }
+    }
+    /// Help shown on the empty canvas.
+    pub fn help_text(&self, ctx: &egui::Context) -> String {
+        let mouse = |action| self.mouse(action).format(ctx);
+        let keys = |action| self.format_keys(ctx, action);
+        [
+            String::from("To add files, drag-and-drop them onto the window."),
+            format!("{} drag - to move the image.", mouse(MouseAction::Pan)),
+            format!(
+                "{} - to select and edit bboxes in the image.",
+                mouse(MouseAction::Select)
+            ),
+            format!(
+                "{} drag - to move edges, corners or the whole selected bbox.",
+                mouse(MouseAction::Edit)
+            ),
+            format!(
+                "{} drag in draw mode - to draw new bboxes.",
+                mouse(MouseAction::Draw)
+            ),
+            format!(
+                "{} - to remove any elements: images, configs, bboxes.",
+                mouse(MouseAction::Delete)
+            ),
+            format!(
+                "{} / {} - to undo / redo bbox changes.",
+                keys(Action::Undo),
+                keys(Action::Redo)
+            ),
+            format!(
+                "{} - to list all keyboard shortcuts.",
+                keys(Action::ToggleHelp)
+            ),
+            String::from("Click on editable config to reset changes."),
+        ]
+        .join("\n\n")
+    }
+}
+
+#[derive(Default)]
+pub struct BindingsSettings {
+    pub open: bool,
+    /// Action waiting for a key press to get a new shortcut.
+    pub recording: Option<Action>,
+}
+
+/// Makes Ctrl on Linux and Windows and Cmd on Mac the same binding.
+fn normalize(mut modifiers: Modifiers) -> Modifiers {
+    if modifiers.command {
+        modifiers.ctrl = false;
+        modifiers.mac_cmd = false;
+    }
+    modifiers
+}
+
+impl RoIApp {
+    pub fn render_bindings_window(&mut self, ctx: &egui::Context) {
+        if let Some(action) = self.bindings_settings.recording {
+            self.record_shortcut(ctx, action);
+        }
+
+        let mut open = self.bindings_settings.open;
+        Window::new("Bindings")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.strong("Keyboard");
+                Grid::new("key_bindings_grid")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for action in Action::ALL {
+                            ui.label(action.label());
+                            ui.horizontal(|ui| {
+                                let mut to_remove = None;
+                                for (idx, (bound, shortcut)) in
+                                    self.bindings.keys.iter().enumerate()
+                                {
+                                    if *bound == action
+                                        && ui
+                                            .small_button(ctx.format_shortcut(shortcut))
+                                            .on_hover_text("Click to remove")
+                                            .clicked()
+                                    {
+                                        to_remove = Some(idx);
+                                    }
+                                }
+                                if let Some(idx) = to_remove {
+                                    self.bindings.keys.remove(idx);
+                                }
+                            });
+                            let recording = &mut self.bindings_settings.recording;
+                            if *recording == Some(action) {
+                                if ui.small_button("press a key...").clicked() {
+                                    *recording = None;
+                                }
+                            } else if ui.small_button("+").clicked() {
+                                *recording = Some(action);
+                            }
+                            ui.end_row();
+                        }
+                    });
+
+                ui.separator();
+                ui.strong("Mouse");
+                Grid::new("mouse_bindings_grid")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for action in MouseAction::ALL {
+                            ui.label(action.label());
+                            let binding = self.bindings.mouse_mut(action);
+                            ui.horizontal(|ui| {
+                                ComboBox::from_id_salt(("mouse_button", action.label()))
+                                    .selected_text(button_name(binding.button))
+                                    .show_ui(ui, |ui| {
+                                        for button in BUTTONS {
+                                            ui.selectable_value(
+                                                &mut binding.button,
+                                                button,
+                                                button_name(button),
+                                            );
+                                        }
+                                    });
+                                ui.checkbox(&mut binding.modifiers.command, "Ctrl/Cmd");
+                                ui.checkbox(&mut binding.modifiers.shift, "Shift");
+                                ui.checkbox(&mut binding.modifiers.alt, "Alt");
+                            });
+                            ui.end_row();
+                        }
+                    });
+
+                ui.separator();
+                if ui.button("Reset to defaults").clicked() {
+                    self.bindings = Bindings::default();
+                    self.bindings_settings.recording = None;
+                }
+            });
+        self.bindings_settings.open = open;
+        if !open {
+            self.bindings_settings.recording = None;
+        }
+    }
+    /// Binds the next pressed key to the action. Escape cancels.
+    fn record_shortcut(&mut self, ctx: &egui::Context, action: Action) {
+        let pressed = ctx.input(|i| {
+            i.events.iter().find_map(|event| match event {
+                Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } => Some((*key, *modifiers)),
+                _ => None,
+            })
+        });
+        let Some((key, modifiers)) = pressed else {
+            return;
+        };
+        // keep the key from also triggering its current action this frame
+        ctx.input_mut(|i| i.consume_key(modifiers, key));
+        self.bindings_settings.recording = None;
+        if key == Key::Escape && modifiers.is_none() {
+            return;
+        }
+        let shortcut = KeyboardShortcut::new(normalize(modifiers), key);
+        // one shortcut does one thing
+        self.bindings.keys.retain(|(_, bound)| *bound != shortcut);
+        self.bindings.keys.push((action, shortcut));
+    }
+}
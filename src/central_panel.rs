@@ -1,18 +1,34 @@
 use crate::app::RoIApp;
+use crate::bindings::MouseAction;
+use crate::config::JsonConfig;
 use crate::config_data::EditCoord;
-use egui::{Color32, Id, PointerButton, RichText, Stroke, Vec2, Vec2b};
+use crate::labels::roi_color;
+use egui::{Color32, Id, RichText, Stroke, Vec2, Vec2b};
 use egui_plot::{
-    AxisHints, HLine, HPlacement, Plot, PlotImage, PlotPoint, PlotPoints, Polygon, VLine,
-    VPlacement,
+    AxisHints, HLine, HPlacement, Line, MarkerShape, Plot, PlotBounds, PlotImage, PlotPoint,
+    PlotPoints, Points, Polygon, VLine, VPlacement,
 };
 use std::ops::Neg;
 
+/// Max distance in screen points from an edge or a corner to grab it.
+const HANDLE_GRAB_DISTANCE: f64 = 10.0;
+/// Distance in screen points of the rotation handle above the top edge of a roi.
+const ROTATE_HANDLE_DISTANCE: f64 = 30.0;
+
 impl RoIApp {
     pub fn render_center_panel(&mut self, ctx: &egui::Context) {
         egui::CentralPanel::default().show(ctx, |ui| {
             let inner_size = ui.available_size();
 
             if let Some(img_data) = &mut self.img_data {
+                let drawing = self.config_data.draw_mode && self.selected_config.is_some();
+                let pan = self.bindings.mouse(MouseAction::Pan);
+                let select = self.bindings.mouse(MouseAction::Select);
+                let edit = self.bindings.mouse(MouseAction::Edit);
+                let draw = self.bindings.mouse(MouseAction::Draw);
+                let delete = self.bindings.mouse(MouseAction::Delete);
+                let img_size = [img_data.width as f64, img_data.height as f64];
+
                 let plot = Plot::new("current_plot")
                     .data_aspect(1.0)
                     .set_margin_fraction(Vec2::new(0., 0.))
@@ -40,6 +56,8 @@ impl RoIApp {
                     })
                     .show_grid(Vec2b::new(true, true))
                     .allow_boxed_zoom(false)
+                    // panning is done below, so it can use any mouse button
+                    .allow_drag(false)
                     .x_axis_position(VPlacement::Top)
                     .custom_x_axes(vec![
                         AxisHints::new_x().placement(VPlacement::Top),
@@ -72,33 +90,137 @@ impl RoIApp {
                     )
                     .allow_hover(false);
                     plot_ui.image(plot_img);
+                    if let Some([x1, y1, x2, y2]) = self.restore_bounds.take() {
+                        plot_ui.set_plot_bounds(PlotBounds::from_min_max([x1, y2], [x2, y1]));
+                    }
+
+                    // the pan button may be shared with drawing or editing, which take precedence
+                    let busy = self.config_data.draw_start.is_some()
+                        || !matches!(self.config_data.edit_coord, EditCoord::None);
+                    if !busy && pan.dragged(plot_ui.response()) {
+                        let delta = plot_ui.pointer_coordinate_drag_delta();
+                        plot_ui.translate_bounds(-delta);
+                    }
 
                     for (idx, config) in self.config_data.config.iter().enumerate() {
                         let [x1, y1, x2, y2] = config
                             .get_abs_plot_coords(img_data.width as f64, img_data.height as f64);
 
-                        if Some(idx) == self.config_data.edit_idx {
+                        if Some(idx) == self.config_data.edit_idx && config.is_rotated() {
+                            let corners = config.corners(img_size).map(to_plot);
+                            plot_ui.line(
+                                Line::new(PlotPoints::new(vec![
+                                    corners[0], corners[1], corners[2], corners[3], corners[0],
+                                ]))
+                                .stroke(Stroke::new(2.0, Color32::GREEN))
+                                .highlight(!matches!(
+                                    self.config_data.edit_coord,
+                                    EditCoord::None | EditCoord::Rotate
+                                ))
+                                .allow_hover(false),
+                            );
+                            plot_ui.points(
+                                Points::new(PlotPoints::new(corners.to_vec()))
+                                    .shape(MarkerShape::Square)
+                                    .radius(5.0)
+                                    .filled(true)
+                                    .color(Color32::GREEN)
+                                    .allow_hover(false),
+                            );
+                        } else if Some(idx) == self.config_data.edit_idx {
+                            let edit_coord = self.config_data.edit_coord;
                             plot_ui.vline(
                                 VLine::new(x1)
-                                    .highlight(matches!(self.config_data.edit_coord, EditCoord::X1))
+                                    .highlight(edit_coord.moves_x1())
                                     .stroke(Stroke::new(2.0, Color32::GREEN)),
                             );
                             plot_ui.hline(
                                 HLine::new(y1)
-                                    .highlight(matches!(self.config_data.edit_coord, EditCoord::Y1))
+                                    .highlight(edit_coord.moves_y1())
                                     .stroke(Stroke::new(2.0, Color32::GREEN)),
                             );
                             plot_ui.vline(
                                 VLine::new(x2)
-                                    .highlight(matches!(self.config_data.edit_coord, EditCoord::X2))
+                                    .highlight(edit_coord.moves_x2())
                                     .stroke(Stroke::new(2.0, Color32::GREEN)),
                             );
                             plot_ui.hline(
                                 HLine::new(y2)
-                                    .highlight(matches!(self.config_data.edit_coord, EditCoord::Y2))
+                                    .highlight(edit_coord.moves_y2())
                                     .stroke(Stroke::new(2.0, Color32::GREEN)),
                             );
+                            plot_ui.points(
+                                Points::new(PlotPoints::new(Vec::<[f64; 2]>::from([
+                                    [x1, y1],
+                                    [x2, y1],
+                                    [x2, y2],
+                                    [x1, y2],
+                                ])))
+                                .shape(MarkerShape::Square)
+                                .radius(5.0)
+                                .filled(true)
+                                .color(Color32::GREEN)
+                                .allow_hover(false),
+                            );
                         } else {
+                            let color = roi_color(&self.labels, config.class());
+                            let points = if config.is_rotated() {
+                                config.corners(img_size).map(to_plot).to_vec()
+                            } else {
+                                vec![[x1, y1], [x2, y1], [x2, y2], [x1, y2]]
+                            };
+                            let polygon_obj = Polygon::new(PlotPoints::new(points))
+                                .fill_color(color.gamma_multiply(0.12))
+                                .name(&config.name)
+                                .stroke(Stroke::new(2.0, color))
+                                .id(Id::new(idx));
+
+                            plot_ui.polygon(polygon_obj);
+                        }
+                    }
+
+                    if let Some(config) = self
+                        .config_data
+                        .edit_idx
+                        .and_then(|idx| self.config_data.config.get(idx))
+                    {
+                        let offset =
+                            ROTATE_HANDLE_DISTANCE * plot_ui.transform().dvalue_dpos()[0].abs();
+                        let corners = config.corners(img_size);
+                        let top_center = [
+                            (corners[0][0] + corners[1][0]) / 2.0,
+                            (corners[0][1] + corners[1][1]) / 2.0,
+                        ];
+                        let handle = to_plot(rotate_handle(config, img_size, offset));
+                        plot_ui.line(
+                            Line::new(PlotPoints::new(vec![to_plot(top_center), handle]))
+                                .stroke(Stroke::new(1.0, Color32::GREEN))
+                                .allow_hover(false),
+                        );
+                        plot_ui.points(
+                            Points::new(PlotPoints::new(vec![handle]))
+                                .shape(MarkerShape::Circle)
+                                .radius(6.0)
+                                .filled(matches!(self.config_data.edit_coord, EditCoord::Rotate))
+                                .color(Color32::GREEN)
+                                .allow_hover(false),
+                        );
+                    }
+
+                    if let Some([sx, sy]) = self.config_data.draw_start {
+                        if let Some(pointer) = plot_ui.pointer_coordinate() {
+                            let [x1, y1, x2, y2] =
+                                img_data.get_rel_config_coords(sx, sy, pointer.x, pointer.y);
+                            let preview = JsonConfig {
+                                x1,
+                                y1,
+                                x2,
+                                y2,
+                                ..Default::default()
+                            };
+                            let [x1, y1, x2, y2] = preview
+                                .get_abs_plot_coords(img_data.width as f64, img_data.height as f64);
+
                             let polygon_obj =
                                 Polygon::new(PlotPoints::new(Vec::<[f64; 2]>::from([
                                     [x1, y1],
@@ -106,10 +228,9 @@ impl RoIApp {
                                     [x2, y2],
                                     [x1, y2],
                                 ])))
-                                .fill_color(Color32::TRANSPARENT)
-                                .name(&config.name)
-                                .stroke(Stroke::new(2.0, Color32::WHITE))
-                                .id(Id::new(idx));
+                                .fill_color(Color32::from_rgba_unmultiplied(0, 255, 0, 32))
+                                .stroke(Stroke::new(2.0, Color32::GREEN))
+                                .allow_hover(false);
 
                             plot_ui.polygon(polygon_obj);
                         }
@@ -122,114 +243,372 @@ impl RoIApp {
                 // account inverted y-axis
                 img_data.bounds = [min[0], max[1], max[0], min[1]];
 
-                if plot_resp.response.middle_clicked() {
+                if drawing {
+                    if draw.drag_started(&plot_resp.response) {
+                        if let Some(pos) = ctx.input(|i| i.pointer.press_origin()) {
+                            let plot_pos = plot_resp.transform.value_from_position(pos);
+                            self.config_data.draw_start = Some([plot_pos.x, plot_pos.y]);
+                        }
+                    }
+                    if draw.drag_stopped(&plot_resp.response) {
+                        if let (Some([sx, sy]), Some(pos)) = (
+                            self.config_data.draw_start.take(),
+                            ctx.pointer_interact_pos(),
+                        ) {
+                            let plot_pos = plot_resp.transform.value_from_position(pos);
+                            let [x1, y1, x2, y2] =
+                                img_data.get_rel_config_coords(sx, sy, plot_pos.x, plot_pos.y);
+
+                            // skip degenerate boxes from accidental clicks
+                            if x1 < x2 && y1 < y2 {
+                                self.config_data.add_roi(JsonConfig {
+                                    x1,
+                                    y1,
+                                    x2,
+                                    y2,
+                                    name: String::from("new_roi"),
+                                    ..Default::default()
+                                });
+                                self.config_data.edit_idx = Some(self.config_data.config.len() - 1);
+                                self.config_data.edit_coord = EditCoord::None;
+                            }
+                        }
+                    }
+                } else {
+                    self.config_data.draw_start = None;
+                }
+
+                if delete.clicked(&plot_resp.response) {
                     if let Some(pos) = ctx.pointer_interact_pos() {
                         let plot_pos = plot_resp.transform.value_from_position(pos);
                         let x = img_data.get_rel_config_coord_x1(plot_pos.x);
                         let y = img_data.get_rel_config_coord_y1(plot_pos.y);
 
-                        if let Some(del_idx) = self.config_data.find_relevant_roi_at_coord(x, y) {
+                        if let Some(del_idx) =
+                            self.config_data.find_relevant_roi_at_coord(x, y, img_size)
+                        {
                             self.config_data.safely_remove_roi(del_idx);
                         }
                     }
                 };
 
-                if plot_resp.response.secondary_clicked() {
+                if select.clicked(&plot_resp.response) {
                     if let Some(pos) = ctx.pointer_interact_pos() {
                         let plot_pos = plot_resp.transform.value_from_position(pos);
                         let x = img_data.get_rel_config_coord_x1(plot_pos.x);
                         let y = img_data.get_rel_config_coord_y1(plot_pos.y);
 
-                        let best_match_idx = self.config_data.find_relevant_roi_at_coord(x, y);
+                        let best_match_idx =
+                            self.config_data.find_relevant_roi_at_coord(x, y, img_size);
                         if best_match_idx.is_some() {
                             self.config_data.edit_idx = best_match_idx;
                         }
                     }
                 };
-                if plot_resp.response.drag_started_by(PointerButton::Secondary) {
-                    if let Some(pos) = ctx.pointer_interact_pos() {
+                if edit.drag_started(&plot_resp.response) {
+                    if let Some(pos) = ctx.input(|i| i.pointer.press_origin()) {
                         let plot_pos = plot_resp.transform.value_from_position(pos);
 
                         if let Some(idx) = self.config_data.edit_idx {
                             let config = &self.config_data.config[idx];
-                            let [x1, y1, x2, y2] = config
+                            let abs_coords = config
                                 .get_abs_plot_coords(img_data.width as f64, img_data.height as f64);
+                            // keep the grab area constant on screen regardless of zoom
+                            let dvalue_dpos = plot_resp.transform.dvalue_dpos()[0].abs();
+                            let threshold = HANDLE_GRAB_DISTANCE * dvalue_dpos;
+                            let pos = [plot_pos.x, plot_pos.y.neg()];
+                            let handle = rotate_handle(
+                                config,
+                                img_size,
+                                ROTATE_HANDLE_DISTANCE * dvalue_dpos,
+                            );
 
-                            let mut best_match = EditCoord::None;
-                            let mut best_val = f64::MAX;
-
-                            let mut val: f64 = (x1 - plot_pos.x).abs();
-                            if (val < 10.0) && (val < best_val) {
-                                best_match = EditCoord::X1;
-                                best_val = val;
-                            }
-                            val = (y1 - plot_pos.y).abs();
-                            if (val < 10.0) && (val < best_val) {
-                                best_match = EditCoord::Y1;
-                                best_val = val;
+                            self.config_data.edit_coord =
+                                if (pos[0] - handle[0]).hypot(pos[1] - handle[1]) < threshold {
+                                    EditCoord::Rotate
+                                } else {
+                                    // edges of a rotated roi are found in its unrotated frame
+                                    match find_edit_coord(
+                                        abs_coords,
+                                        [config.x1, config.y1, config.x2, config.y2],
+                                        to_plot(unrotate(config, img_size, pos)),
+                                        threshold,
+                                    ) {
+                                        EditCoord::Body { origin, .. } => EditCoord::Body {
+                                            grab: [plot_pos.x, plot_pos.y],
+                                            origin,
+                                        },
+                                        edit_coord => edit_coord,
+                                    }
+                                };
+                            if !matches!(self.config_data.edit_coord, EditCoord::None) {
+                                self.config_data.history.begin(&self.config_data.config);
                             }
-                            val = (x2 - plot_pos.x).abs();
-                            if (val < 10.0) && (val < best_val) {
-                                best_match = EditCoord::X2;
-                                best_val = val;
-                            }
-                            val = (y2 - plot_pos.y).abs();
-                            if (val < 10.0) && (val < best_val) {
-                                best_match = EditCoord::Y2;
-                                // best_val = val;
-                            }
-                            self.config_data.edit_coord = best_match;
                         }
                     }
                 }
-                if plot_resp.response.drag_stopped_by(PointerButton::Secondary) {
+                if edit.drag_stopped(&plot_resp.response) {
                     self.config_data.edit_coord = EditCoord::None;
+                    self.config_data.history.finish(&self.config_data.config);
                 }
-                if plot_resp.response.dragged_by(PointerButton::Secondary) {
+                if edit.dragged(&plot_resp.response) {
                     if let Some(pos) = ctx.pointer_interact_pos() {
                         let plot_pos = plot_resp.transform.value_from_position(pos);
 
                         if let Some(idx) = self.config_data.edit_idx {
                             let config = &mut self.config_data.config[idx];
                             match self.config_data.edit_coord {
-                                EditCoord::X1 => {
-                                    config.x1 =
-                                        img_data.get_rel_config_coord_x1(plot_pos.x).min(config.x2)
-                                }
-                                EditCoord::Y1 => {
-                                    config.y1 =
-                                        img_data.get_rel_config_coord_y1(plot_pos.y).min(config.y2)
+                                EditCoord::Body { grab, origin } => {
+                                    let [ox1, oy1, ox2, oy2] = origin;
+                                    let width = img_data.width as f64;
+                                    let height = img_data.height as f64;
+                                    // the corners of a rotated roi stay in the image too
+                                    let [ex1, ey1, ex2, ey2] = JsonConfig {
+                                        x1: ox1,
+                                        y1: oy1,
+                                        x2: ox2,
+                                        y2: oy2,
+                                        angle: config.angle,
+                                        ..Default::default()
+                                    }
+                                    .envelope(img_size);
+                                    // move by whole pixels, so the roi size doesn't drift
+                                    let dx = ((plot_pos.x - grab[0]).round() / width)
+                                        .clamp(-ex1, 1.0 - ex2);
+                                    let dy = ((grab[1] - plot_pos.y).round() / height)
+                                        .clamp(-ey1, 1.0 - ey2);
+                                    config.x1 = ox1 + dx;
+                                    config.y1 = oy1 + dy;
+                                    config.x2 = ox2 + dx;
+                                    config.y2 = oy2 + dy;
                                 }
-                                EditCoord::X2 => {
-                                    config.x2 =
-                                        img_data.get_rel_config_coord_x2(plot_pos.x).max(config.x1)
-                                }
-                                EditCoord::Y2 => {
-                                    config.y2 =
-                                        img_data.get_rel_config_coord_y2(plot_pos.y).max(config.y1)
+                                EditCoord::Rotate => {
+                                    let [cx, cy] = config.get_center();
+                                    let dx = plot_pos.x - cx * img_size[0];
+                                    let dy = plot_pos.y.neg() - cy * img_size[1];
+                                    // the handle points up at zero angle
+                                    let angle = dx.atan2(dy.neg()).to_degrees();
+                                    let rotated = JsonConfig {
+                                        angle: (angle * 10.0).round() / 10.0,
+                                        ..config.clone()
+                                    };
+                                    // stop turning where a corner would leave the image
+                                    if rotated.is_inside(img_size) || !config.is_inside(img_size) {
+                                        *config = rotated;
+                                    }
                                 }
                                 EditCoord::None => {}
+                                edit_coord if config.is_rotated() => resize_rotated(
+                                    config,
+                                    edit_coord,
+                                    [plot_pos.x, plot_pos.y.neg()],
+                                    img_size,
+                                ),
+                                edit_coord => {
+                                    if edit_coord.moves_x1() {
+                                        config.x1 = img_data
+                                            .get_rel_config_coord_x1(plot_pos.x)
+                                            .min(config.x2);
+                                    }
+                                    if edit_coord.moves_y1() {
+                                        config.y1 = img_data
+                                            .get_rel_config_coord_y1(plot_pos.y)
+                                            .min(config.y2);
+                                    }
+                                    if edit_coord.moves_x2() {
+                                        config.x2 = img_data
+                                            .get_rel_config_coord_x2(plot_pos.x)
+                                            .max(config.x1);
+                                    }
+                                    if edit_coord.moves_y2() {
+                                        config.y2 = img_data
+                                            .get_rel_config_coord_y2(plot_pos.y)
+                                            .max(config.y1);
+                                    }
+                                }
                             }
                         }
                     }
                 }
+            } else if self.image_loader.is_loading() {
+                ui.centered_and_justified(|ui| ui.spinner());
             } else {
                 ui.centered_and_justified(|ui| {
                     ui.label(
-                        RichText::new(
-                            "To add files, drag-and-drop them onto the window.
-
-                            RightClick - to select and edit bboxes in the image.
-
-                            MiddleClick - to remove any elements: images, configs, bboxes.
-
-                            Click on editable config to reset changes.",
-                        )
-                        .heading()
-                        .size(25.0),
+                        RichText::new(self.bindings.help_text(ctx))
+                            .heading()
+                            .size(25.0),
                     );
                 });
             };
         });
     }
 }
+
+/// Pixel coords with y-axis pointing down to plot coords.
+fn to_plot([x, y]: [f64; 2]) -> [f64; 2] {
+    [x, y.neg()]
+}
+
+/// Rotation handle in pixels, straight above the top edge of the roi when unrotated.
+fn rotate_handle(roi: &JsonConfig, img_size: [f64; 2], offset: f64) -> [f64; 2] {
+    let [width, height] = img_size;
+    let [cx, cy] = roi.get_center();
+    let dist = (roi.y2 - roi.y1) * height / 2.0 + offset;
+    let (sin, cos) = roi.angle.to_radians().sin_cos();
+    [cx * width + dist * sin, cy * height - dist * cos]
+}
+
+/// Pixel position rotated back around the center of the roi, into its unrotated frame.
+fn unrotate(roi: &JsonConfig, img_size: [f64; 2], [x, y]: [f64; 2]) -> [f64; 2] {
+    let [cx, cy] = roi.get_center();
+    let [cx, cy] = [cx * img_size[0], cy * img_size[1]];
+    let [dx, dy] = [x - cx, y - cy];
+    let (sin, cos) = roi.angle.to_radians().sin_cos();
+    [cx + dx * cos + dy * sin, cy + dy * cos - dx * sin]
+}
+
+/// Moves the grabbed sides of a rotated roi to the pixel position, the opposite ones stay put.
+/// The sides stop where a corner would leave the image.
+fn resize_rotated(roi: &mut JsonConfig, edit_coord: EditCoord, pos: [f64; 2], img_size: [f64; 2]) {
+    let [width, height] = img_size;
+    let [cx, cy] = roi.get_center();
+    let [cx, cy] = [cx * width, cy * height];
+    let [ux, uy] = unrotate(roi, img_size, pos);
+    let [px, py] = [ux - cx, uy - cy];
+    // sides relative to the center, along the roi axes
+    let half_w = (roi.x2 - roi.x1) * width / 2.0;
+    let half_h = (roi.y2 - roi.y1) * height / 2.0;
+    let sides = [-half_w, -half_h, half_w, half_h];
+    let [mut x1, mut y1, mut x2, mut y2] = sides;
+    if edit_coord.moves_x1() {
+        x1 = px.min(x2);
+    }
+    if edit_coord.moves_y1() {
+        y1 = py.min(y2);
+    }
+    if edit_coord.moves_x2() {
+        x2 = px.max(x1);
+    }
+    if edit_coord.moves_y2() {
+        y2 = py.max(y1);
+    }
+    let (sin, cos) = roi.angle.to_radians().sin_cos();
+    let resized = |[x1, y1, x2, y2]: [f64; 4]| {
+        let [mx, my] = [(x1 + x2) / 2.0, (y1 + y2) / 2.0];
+        let [cx, cy] = [cx + mx * cos - my * sin, cy + mx * sin + my * cos];
+        let [half_w, half_h] = [(x2 - x1) / 2.0, (y2 - y1) / 2.0];
+        JsonConfig {
+            x1: (cx - half_w) / width,
+            y1: (cy - half_h) / height,
+            x2: (cx + half_w) / width,
+            y2: (cy + half_h) / height,
+            ..roi.clone()
+        }
+    };
+    let target = [x1, y1, x2, y2];
+    if resized(target).is_inside(img_size) || !roi.is_inside(img_size) {
+        *roi = resized(target);
+        return;
+    }
+    // the furthest step towards the pointer that still fits
+    let step =
+        |t: f64| -> [f64; 4] { std::array::from_fn(|i| sides[i] + t * (target[i] - sides[i])) };
+    let [mut fits, mut exceeds] = [0.0, 1.0];
+    for _ in 0..20 {
+        let t = (fits + exceeds) / 2.0;
+        if resized(step(t)).is_inside(img_size) {
+            fits = t;
+        } else {
+            exceeds = t;
+        }
+    }
+    *roi = resized(step(fits));
+}
+
+/// Picks what to drag in a roi given by absolute plot coords `[x1, y1, x2, y2]`
+/// and the same roi in relative config coords.
+/// Corners take precedence over edges, edges over the interior.
+fn find_edit_coord(
+    abs_coords: [f64; 4],
+    rel_coords: [f64; 4],
+    pos: [f64; 2],
+    threshold: f64,
+) -> EditCoord {
+    let [x1, y1, x2, y2] = abs_coords;
+    let [x, y] = pos;
+
+    let dx1 = (x1 - x).abs();
+    let dy1 = (y1 - y).abs();
+    let dx2 = (x2 - x).abs();
+    let dy2 = (y2 - y).abs();
+
+    let mut best_match = EditCoord::None;
+    let mut best_val = f64::MAX;
+    for (edit_coord, dx, dy) in [
+        (EditCoord::X1Y1, dx1, dy1),
+        (EditCoord::X2Y1, dx2, dy1),
+        (EditCoord::X2Y2, dx2, dy2),
+        (EditCoord::X1Y2, dx1, dy2),
+    ] {
+        let val = dx.max(dy);
+        if (val < threshold) && (val < best_val) {
+            best_match = edit_coord;
+            best_val = val;
+        }
+    }
+    if !matches!(best_match, EditCoord::None) {
+        return best_match;
+    }
+
+    for (edit_coord, val) in [
+        (EditCoord::X1, dx1),
+        (EditCoord::Y1, dy1),
+        (EditCoord::X2, dx2),
+        (EditCoord::Y2, dy2),
+    ] {
+        if (val < threshold) && (val < best_val) {
+            best_match = edit_coord;
+            best_val = val;
+        }
+    }
+    if !matches!(best_match, EditCoord::None) {
+        return best_match;
+    }
+
+    // plot y-axis is inverted, so y2 is below y1
+    if (x1..=x2).contains(&x) && (y2..=y1).contains(&y) {
+        return EditCoord::Body {
+            grab: pos,
+            origin: rel_coords,
+        };
+    }
+    EditCoord::None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotated_resize_stops_at_the_image_border() {
+        let img_size = [200.0, 100.0];
+        // 40x20 px turned by 45 degrees around the middle
+        let mut roi = JsonConfig {
+            x1: 0.4,
+            y1: 0.4,
+            x2: 0.6,
+            y2: 0.6,
+            angle: 45.0,
+            ..Default::default()
+        };
+        resize_rotated(&mut roi, EditCoord::X2, [1000.0, 1000.0], img_size);
+        assert!(roi.is_inside(img_size));
+        let [.., x2, y2] = roi.envelope(img_size);
+        assert!(
+            (1.0 - x2).min(1.0 - y2) < 1e-3,
+            "stopped short at {:?}",
+            [x2, y2]
+        );
+    }
+}
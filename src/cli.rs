@@ -0,0 +1,234 @@
+use crate::config::{read_config, read_config_file, write_config, JsonConfig, CONFIG_VERSION};
+use crate::crop::{crop_images, CropFormat, CropOptions, DEFAULT_CROP_TEMPLATE};
+use crate::dataset::load_items;
+use crate::export::ExportFormat;
+use crate::orientation::RoiFrame;
+use crate::pairing::find_image_of_config;
+use anyhow::bail;
+use clap::{Args, Parser, Subcommand};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Region of interest annotation tool. Starts the GUI when no command is given.
+#[derive(Parser)]
+#[command(version, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[command(flatten)]
+    pub open: OpenArgs,
+}
+
+/// Files to open in the GUI at startup.
+#[derive(Args, Default)]
+pub struct OpenArgs {
+    /// Images, configs, annotation files, a .roiproj project or directories
+    pub paths: Vec<PathBuf>,
+    /// Image to select
+    #[arg(long)]
+    pub image: Option<PathBuf>,
+    /// Config to select, created on save if it doesn't exist
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Check that configs parse and all rois lie inside the image
+    Validate {
+        /// Config files: RoI json or YOLO txt
+        #[arg(required = true)]
+        configs: Vec<PathBuf>,
+    },
+    /// Rewrite legacy json configs in the versioned format, recording their images
+    Upgrade {
+        /// RoI json configs
+        #[arg(required = true)]
+        configs: Vec<PathBuf>,
+    },
+    /// Convert annotations between formats
+    Convert {
+        /// Images and their annotations: RoI json, COCO json, YOLO txt, Pascal VOC xml, RoI project
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+        /// Output format
+        #[arg(long, value_enum)]
+        to: ExportFormat,
+        /// Output file for COCO, output directory for other formats
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Whether roi coords follow the EXIF orientation or the stored pixels
+        #[arg(long, value_enum, default_value_t)]
+        frame: RoiFrame,
+    },
+    /// Print roi counts and sizes
+    Stats {
+        /// Images and their annotations: RoI json, COCO json, YOLO txt, Pascal VOC xml, RoI project
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+        /// Whether roi coords follow the EXIF orientation or the stored pixels
+        #[arg(long, value_enum, default_value_t)]
+        frame: RoiFrame,
+    },
+    /// Save every roi as a separate image named after the roi
+    Crop {
+        /// Images and their annotations: RoI json, COCO json, YOLO txt, Pascal VOC xml, RoI project
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+        /// Output directory, may contain `{dir}` image directory,
+        /// `{image}` image file stem and `{name}` roi name
+        #[arg(short, long, default_value = DEFAULT_CROP_TEMPLATE)]
+        output: String,
+        /// Pixels added on every side of a roi
+        #[arg(long, default_value_t = 0)]
+        padding: u32,
+        /// Resize crops to exactly WIDTHxHEIGHT, e.g. 224x224
+        #[arg(long, value_parser = parse_size)]
+        resize: Option<[u32; 2]>,
+        /// Image format of the crops
+        #[arg(long, value_enum, default_value_t)]
+        format: CropFormat,
+        /// Whether roi coords follow the EXIF orientation or the stored pixels
+        #[arg(long, value_enum, default_value_t)]
+        frame: RoiFrame,
+    },
+}
+
+impl Command {
+    pub fn run(self) -> anyhow::Result<()> {
+        match self {
+            Self::Validate { configs } => validate(&configs),
+            Self::Upgrade { configs } => upgrade(&configs),
+            Self::Convert {
+                inputs,
+                to,
+                output,
+                frame,
+            } => {
+                let items = load_items(&inputs, frame)?;
+                to.export(&items, &output, frame)?;
+                println!("Wrote {}", output.display());
+                Ok(())
+            }
+            Self::Stats { inputs, frame } => {
+                print_stats(&load_items(&inputs, frame)?);
+                Ok(())
+            }
+            Self::Crop {
+                inputs,
+                output,
+                padding,
+                resize,
+                format,
+                frame,
+            } => {
+                let options = CropOptions {
+                    padding,
+                    resize,
+                    format,
+                    template: output,
+                    frame,
+                };
+                for path in crop_images(&load_items(&inputs, frame)?, &options)? {
+                    println!("{}", path.display());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn parse_size(value: &str) -> Result<[u32; 2], String> {
+    let parse = |val: &str| val.trim().parse::<u32>().ok().filter(|val| *val > 0);
+    match value
+        .split_once(['x', 'X'])
+        .map(|(w, h)| (parse(w), parse(h)))
+    {
+        Some((Some(width), Some(height))) => Ok([width, height]),
+        _ => Err(format!("expected WIDTHxHEIGHT, got {:?}", value)),
+    }
+}
+
+fn validate(configs: &[PathBuf]) -> anyhow::Result<()> {
+    let mut n_invalid = 0usize;
+    for path in configs {
+        let errors: Vec<String> = match read_config(path) {
+            Ok(config) => config
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, roi)| roi.validate().err().map(|e| format!("#{}: {}", idx, e)))
+                .collect(),
+            Err(err) => vec![format!("{:#}", err)],
+        };
+        if errors.is_empty() {
+            println!("OK      {}", path.display());
+        } else {
+            n_invalid += 1;
+            println!("INVALID {}", path.display());
+            for error in errors {
+                println!("    {}", error);
+            }
+        }
+    }
+    if n_invalid > 0 {
+        bail!("{} of {} configs are invalid", n_invalid, configs.len());
+    }
+    Ok(())
+}
+
+fn upgrade(configs: &[PathBuf]) -> anyhow::Result<()> {
+    for path in configs {
+        let config_file = read_config_file(path)?;
+        if config_file.version == CONFIG_VERSION {
+            println!("up to date {}", path.display());
+            continue;
+        }
+        let img_path = find_image_of_config(path);
+        write_config(path, &config_file.rois, img_path.as_deref())?;
+        match img_path {
+            Some(img_path) => println!("upgraded   {} ({})", path.display(), img_path.display()),
+            None => println!("upgraded   {} (image not found)", path.display()),
+        }
+    }
+    Ok(())
+}
+
+fn print_stats(items: &[(PathBuf, Vec<JsonConfig>)]) {
+    let n_rois: usize = items.iter().map(|(_, config)| config.len()).sum();
+    let n_empty = items.iter().filter(|(_, config)| config.is_empty()).count();
+    println!("images:              {}", items.len());
+    println!("images without rois: {}", n_empty);
+    println!("rois:                {}", n_rois);
+
+    // per label: count, sum of relative widths and heights
+    let mut per_name: BTreeMap<&str, (usize, f64, f64)> = BTreeMap::new();
+    for roi in items.iter().flat_map(|(_, config)| config) {
+        let entry = per_name.entry(roi.class()).or_default();
+        entry.0 += 1;
+        entry.1 += roi.x2 - roi.x1;
+        entry.2 += roi.y2 - roi.y1;
+    }
+    if per_name.is_empty() {
+        return;
+    }
+    let name_width = per_name
+        .keys()
+        .map(|name| name.len())
+        .max()
+        .unwrap_or(0)
+        .max(5);
+    println!();
+    println!(
+        "{:<name_width$}  {:>7}  {:>9}  {:>10}",
+        "label", "count", "avg width", "avg height"
+    );
+    for (name, (count, width, height)) in per_name {
+        println!(
+            "{:<name_width$}  {:>7}  {:>9.4}  {:>10.4}",
+            name,
+            count,
+            width / count as f64,
+            height / count as f64
+        );
+    }
+}
@@ -0,0 +1,311 @@
+use crate::config::JsonConfig;
+use crate::orientation::{image_size, RoiFrame};
+use anyhow::Context;
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// Subset of the COCO detection format, enough to round-trip bboxes.
+#[derive(Serialize, Deserialize)]
+pub struct CocoDataset {
+    pub images: Vec<CocoImage>,
+    #[serde(default)]
+    pub annotations: Vec<CocoAnnotation>,
+    #[serde(default)]
+    pub categories: Vec<CocoCategory>,
+}
+#[derive(Serialize, Deserialize)]
+pub struct CocoImage {
+    pub id: u64,
+    pub file_name: String,
+    pub width: usize,
+    pub height: usize,
+}
+#[derive(Serialize, Deserialize)]
+pub struct CocoAnnotation {
+    pub id: u64,
+    pub image_id: u64,
+    pub category_id: u64,
+    /// Absolute `[x, y, width, height]` in pixels.
+    pub bbox: [f64; 4],
+    #[serde(default)]
+    pub area: f64,
+    #[serde(default)]
+    pub segmentation: Vec<Vec<f64>>,
+    #[serde(default)]
+    pub iscrowd: u8,
+}
+#[derive(Serialize, Deserialize)]
+pub struct CocoCategory {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub supercategory: String,
+}
+
+/// Builds a COCO dataset from images and their rois.
+/// Image sizes are read from the image files, in the `frame` the rois refer to.
+/// `file_name`s are relative to `root` for images inside it and absolute otherwise.
+pub fn to_coco(
+    items: &[(PathBuf, Vec<JsonConfig>)],
+    root: &Path,
+    frame: RoiFrame,
+) -> anyhow::Result<CocoDataset> {
+    let names: BTreeSet<&str> = items
+        .iter()
+        .flat_map(|(_, config)| config.iter().map(|roi| roi.class()))
+        .collect();
+    let categories: Vec<CocoCategory> = names
+        .into_iter()
+        .zip(1u64..)
+        .map(|(name, id)| CocoCategory {
+            id,
+            name: name.to_string(),
+            supercategory: String::new(),
+        })
+        .collect();
+
+    let abs_root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let mut images = Vec::with_capacity(items.len());
+    let mut annotations = Vec::new();
+    for ((img_path, config), image_id) in items.iter().zip(1u64..) {
+        let [width, height] = image_size(img_path, frame)?;
+        let (w, h) = (width as f64, height as f64);
+
+        for roi in config {
+            let bbox = roi.bounding_box([w, h]);
+            let [x1, y1, x2, y2] = [bbox.x1 * w, bbox.y1 * h, bbox.x2 * w, bbox.y2 * h];
+            // rotated rois keep their corners in the segmentation
+            let corners = roi.corners([w, h]);
+            let category_id = categories
+                .iter()
+                .find(|category| category.name == roi.class())
+                .map_or(0, |category| category.id);
+            annotations.push(CocoAnnotation {
+                id: annotations.len() as u64 + 1,
+                image_id,
+                category_id,
+                bbox: [x1, y1, x2 - x1, y2 - y1],
+                area: (roi.x2 - roi.x1) * w * (roi.y2 - roi.y1) * h,
+                segmentation: vec![corners.concat()],
+                iscrowd: 0,
+            });
+        }
+
+        let abs_img_path = fs::canonicalize(img_path).unwrap_or_else(|_| img_path.to_path_buf());
+        let file_name = abs_img_path
+            .strip_prefix(&abs_root)
+            .unwrap_or(&abs_img_path);
+        images.push(CocoImage {
+            id: image_id,
+            file_name: file_name.to_string_lossy().replace('\\', "/"),
+            width: width as usize,
+            height: height as usize,
+        });
+    }
+
+    Ok(CocoDataset {
+        images,
+        annotations,
+        categories,
+    })
+}
+
+/// Splits a COCO dataset into per-image rois.
+/// Image paths are resolved relative to `root`, roi names are taken from categories.
+/// Images without a size in the dataset get it from `size_of` the image file.
+pub fn from_coco(
+    dataset: &CocoDataset,
+    root: &Path,
+    size_of: impl Fn(&Path) -> Option<[usize; 2]>,
+) -> anyhow::Result<Vec<(PathBuf, Vec<JsonConfig>)>> {
+    let mut items = Vec::with_capacity(dataset.images.len());
+    for img in &dataset.images {
+        let img_path = root.join(&img.file_name);
+        let [width, height] = match [img.width, img.height] {
+            [width, height] if width > 0 && height > 0 => [width, height],
+            _ => size_of(&img_path)
+                .with_context(|| format!("unknown size of {}", img_path.display()))?,
+        };
+        let (w, h) = (width as f64, height as f64);
+        let config = dataset
+            .annotations
+            .iter()
+            .filter(|ann| ann.image_id == img.id)
+            .map(|ann| {
+                let [x, y, bw, bh] = ann.bbox;
+                let name = dataset
+                    .categories
+                    .iter()
+                    .find(|category| category.id == ann.category_id)
+                    .map_or_else(|| ann.category_id.to_string(), |c| c.name.clone());
+                JsonConfig {
+                    x1: (x / w).clamp(0.0, 1.0),
+                    y1: (y / h).clamp(0.0, 1.0),
+                    x2: ((x + bw) / w).clamp(0.0, 1.0),
+                    y2: ((y + bh) / h).clamp(0.0, 1.0),
+                    name,
+                    ..Default::default()
+                }
+            })
+            .collect();
+        items.push((img_path, config));
+    }
+    Ok(items)
+}
+
+pub fn export_coco(
+    items: &[(PathBuf, Vec<JsonConfig>)],
+    path: &Path,
+    frame: RoiFrame,
+) -> anyhow::Result<()> {
+    let root = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(root).with_context(|| format!("creating {}", root.display()))?;
+    let dataset = to_coco(items, root, frame)?;
+    let json_string = serde_json::to_string_pretty(&dataset)?;
+    fs::write(path, json_string).with_context(|| format!("writing {}", path.display()))
+}
+/// A COCO dataset is an object with `images`, a RoI config is an array or an object with `rois`.
+/// Only the top level keys are looked at, the file may still be invalid.
+pub fn looks_like_coco(path: &Path) -> bool {
+    let Ok(file) = fs::File::open(path) else {
+        return false;
+    };
+    serde_json::from_reader::<_, HashMap<String, IgnoredAny>>(BufReader::new(file))
+        .is_ok_and(|object| object.contains_key("images") && !object.contains_key("rois"))
+}
+/// Reads a COCO dataset into rois of its images, see [`from_coco`].
+pub fn import_coco(
+    path: &Path,
+    size_of: impl Fn(&Path) -> Option<[usize; 2]>,
+) -> anyhow::Result<Vec<(PathBuf, Vec<JsonConfig>)>> {
+    let json_string =
+        fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let dataset: CocoDataset = serde_json::from_str(&json_string)
+        .with_context(|| format!("parsing {}", path.display()))?;
+    let root = path.parent().unwrap_or(Path::new(""));
+    from_coco(&dataset, root, size_of).with_context(|| format!("importing {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{test_dir, write_png};
+
+    fn full_roi(name: &str) -> JsonConfig {
+        JsonConfig {
+            x2: 1.0,
+            y2: 1.0,
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn pixel_boxes_follow_exif_orientation() {
+        let dir = test_dir("coco_oriented");
+        let img_path = dir.join("img.png");
+        // stored 4x2, shown 2x4
+        write_png(&img_path, [4, 2], 6);
+        let items = vec![(img_path, vec![full_roi("cat")])];
+
+        let dataset = to_coco(&items, &dir, RoiFrame::Oriented).unwrap();
+        assert_eq!([dataset.images[0].width, dataset.images[0].height], [2, 4]);
+        assert_eq!(dataset.annotations[0].bbox, [0.0, 0.0, 2.0, 4.0]);
+
+        let dataset = to_coco(&items, &dir, RoiFrame::Raw).unwrap();
+        assert_eq!([dataset.images[0].width, dataset.images[0].height], [4, 2]);
+        assert_eq!(dataset.annotations[0].bbox, [0.0, 0.0, 4.0, 2.0]);
+    }
+
+    #[test]
+    fn export_and_import_round_trip() {
+        let dir = test_dir("coco_round_trip");
+        fs::create_dir_all(dir.join("images")).unwrap();
+        let img_path = dir.join("images").join("img.png");
+        write_png(&img_path, [10, 20], 1);
+        let config = vec![
+            JsonConfig {
+                x1: 0.1,
+                y1: 0.25,
+                x2: 0.5,
+                y2: 0.75,
+                name: String::from("dog"),
+                ..Default::default()
+            },
+            full_roi("cat"),
+        ];
+        let coco_path = dir.join("instances.json");
+        export_coco(
+            &[(img_path.clone(), config.clone())],
+            &coco_path,
+            RoiFrame::Oriented,
+        )
+        .unwrap();
+
+        let dataset: CocoDataset =
+            serde_json::from_str(&fs::read_to_string(&coco_path).unwrap()).unwrap();
+        assert_eq!(dataset.images[0].file_name, "images/img.png");
+        let names: Vec<&str> = dataset.categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["cat", "dog"]);
+        assert_eq!(dataset.annotations[0].category_id, 2);
+        assert_eq!(dataset.annotations[0].bbox, [1.0, 5.0, 4.0, 10.0]);
+
+        let items = import_coco(&coco_path, |_| None).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].0, img_path);
+        for (imported, roi) in items[0].1.iter().zip(&config) {
+            assert_eq!(imported.name, roi.name);
+            for (a, b) in [
+                (imported.x1, roi.x1),
+                (imported.y1, roi.y1),
+                (imported.x2, roi.x2),
+                (imported.y2, roi.y2),
+            ] {
+                assert!((a - b).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_categories_keep_their_id_and_boxes_are_clamped() {
+        let dataset: CocoDataset = serde_json::from_str(
+            r#"{
+                "images": [{"id": 7, "file_name": "a.png", "width": 10, "height": 10}],
+                "annotations": [
+                    {"id": 1, "image_id": 7, "category_id": 3, "bbox": [-2, 5, 20, 2]},
+                    {"id": 2, "image_id": 8, "category_id": 3, "bbox": [0, 0, 1, 1]}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let items = from_coco(&dataset, Path::new("root"), |_| None).unwrap();
+        assert_eq!(items[0].0, Path::new("root").join("a.png"));
+        let [roi] = &items[0].1[..] else {
+            panic!("expected a single roi, got {}", items[0].1.len());
+        };
+        assert_eq!(roi.name, "3");
+        assert_eq!([roi.x1, roi.y1, roi.x2, roi.y2], [0.0, 0.5, 1.0, 0.7]);
+    }
+
+    #[test]
+    fn images_without_size_take_it_from_the_file() {
+        let dataset: CocoDataset = serde_json::from_str(
+            r#"{
+                "images": [{"id": 1, "file_name": "a.png", "width": 0, "height": 0}],
+                "annotations": [{"id": 1, "image_id": 1, "category_id": 1, "bbox": [2, 5, 4, 5]}]
+            }"#,
+        )
+        .unwrap();
+        assert!(from_coco(&dataset, Path::new("root"), |_| None).is_err());
+        let items = from_coco(&dataset, Path::new("root"), |_| Some([8, 10])).unwrap();
+        let roi = &items[0].1[0];
+        assert_eq!([roi.x1, roi.y1, roi.x2, roi.y2], [0.25, 0.5, 0.75, 1.0]);
+    }
+}
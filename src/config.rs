@@ -1,19 +1,134 @@
+use crate::orientation::{image_size, RoiFrame};
+use crate::project::relative_path;
+use crate::yolo::{read_yolo, write_yolo};
+use anyhow::{bail, Context};
+use image::ImageReader;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::{BufReader, Cursor};
 use std::ops::Neg;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-#[derive(Serialize, Deserialize)]
+/// Value of a roi attribute, stored in json as a plain bool, number or string,
+/// an enum as `{"value": .., "options": [..]}`.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum AttrValue {
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Enum { value: String, options: Vec<String> },
+}
+
+/// Roi in relative coords with y-axis pointing down.
+/// Files with only coords and `name` still read fine, the rest is optional.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct JsonConfig {
     pub x1: f64,
     pub y1: f64,
     pub x2: f64,
     pub y2: f64,
+    /// Instance name, also the class when there's no `label`.
     pub name: String,
+    /// Unique within the config, `0` until assigned with [`assign_ids`].
+    #[serde(default, skip_serializing_if = "is_unassigned")]
+    pub id: u64,
+    /// Class of the roi.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub label: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, AttrValue>,
+    /// Rotation in degrees around the center, clockwise on the image. The coords are those
+    /// of the roi before rotating it.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub angle: f64,
+}
+fn is_unassigned(id: &u64) -> bool {
+    *id == 0
+}
+fn is_zero(angle: &f64) -> bool {
+    *angle == 0.0
 }
 
 impl JsonConfig {
+    /// Class used for labels, colors and exports: the `label`, or the `name` without one.
+    pub fn class(&self) -> &str {
+        if self.label.is_empty() {
+            &self.name
+        } else {
+            &self.label
+        }
+    }
     pub fn get_center(&self) -> [f64; 2] {
         [(self.x2 + self.x1) / 2.0, (self.y2 + self.y1) / 2.0]
     }
+    pub fn is_rotated(&self) -> bool {
+        self.angle != 0.0
+    }
+    /// Corners in pixels of an image of `[width, height]` with y-axis pointing down,
+    /// starting from the top left one of the unrotated roi, clockwise.
+    pub fn corners(&self, [width, height]: [f64; 2]) -> [[f64; 2]; 4] {
+        let [cx, cy] = self.get_center();
+        let [cx, cy] = [cx * width, cy * height];
+        let half_w = (self.x2 - self.x1) * width / 2.0;
+        let half_h = (self.y2 - self.y1) * height / 2.0;
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        [
+            [-half_w, -half_h],
+            [half_w, -half_h],
+            [half_w, half_h],
+            [-half_w, half_h],
+        ]
+        .map(|[x, y]| [cx + x * cos - y * sin, cy + x * sin + y * cos])
+    }
+    /// Relative `[x1, y1, x2, y2]` enclosing the corners, may exceed the image.
+    pub fn envelope(&self, img_size: [f64; 2]) -> [f64; 4] {
+        let [width, height] = img_size;
+        let corners = self.corners(img_size);
+        let xs = corners.map(|[x, _]| x / width);
+        let ys = corners.map(|[_, y]| y / height);
+        [
+            xs.into_iter().fold(f64::MAX, f64::min),
+            ys.into_iter().fold(f64::MAX, f64::min),
+            xs.into_iter().fold(f64::MIN, f64::max),
+            ys.into_iter().fold(f64::MIN, f64::max),
+        ]
+    }
+    /// Whether every corner is in an image of `[width, height]`.
+    pub fn is_inside(&self, img_size: [f64; 2]) -> bool {
+        // corners of an upright roi at the border are off by rounding only
+        const TOLERANCE: f64 = 1e-9;
+        self.envelope(img_size)
+            .iter()
+            .all(|val| (-TOLERANCE..=1.0 + TOLERANCE).contains(val))
+    }
+    /// Axis-aligned roi enclosing the rotated one, limited to the image.
+    pub fn bounding_box(&self, img_size: [f64; 2]) -> JsonConfig {
+        if !self.is_rotated() {
+            return self.clone();
+        }
+        let [x1, y1, x2, y2] = self.envelope(img_size).map(|val| val.clamp(0.0, 1.0));
+        JsonConfig {
+            x1,
+            y1,
+            x2,
+            y2,
+            angle: 0.0,
+            ..self.clone()
+        }
+    }
+    /// Whether the point in relative coords is inside the roi, respecting its rotation.
+    pub fn contains(&self, [x, y]: [f64; 2], [width, height]: [f64; 2]) -> bool {
+        let [cx, cy] = self.get_center();
+        // pixel offset from the center, rotated back to the roi axes
+        let [dx, dy] = [(x - cx) * width, (y - cy) * height];
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let [lx, ly] = [dx * cos + dy * sin, dy * cos - dx * sin];
+        lx.abs() <= (self.x2 - self.x1) * width / 2.0
+            && ly.abs() <= (self.y2 - self.y1) * height / 2.0
+    }
     pub fn get_abs_plot_coords(&self, img_width: f64, img_height: f64) -> [f64; 4] {
         let x1 = (self.x1 * img_width).floor();
         let y1 = (self.y1 * img_height).neg().ceil();
@@ -21,4 +136,448 @@ impl JsonConfig {
         let y2 = (self.y2 * img_height).neg().floor();
         [x1, y1, x2, y2]
     }
+    /// Same rounding as [`JsonConfig::get_abs_plot_coords`], but with y-axis pointing down.
+    pub fn get_abs_pixel_coords(&self, img_width: f64, img_height: f64) -> [f64; 4] {
+        let [x1, y1, x2, y2] = self.get_abs_plot_coords(img_width, img_height);
+        [x1, y1.neg(), x2, y2.neg()]
+    }
+    /// Checks the coords are in the image. The corners of a rotated roi depend on the image
+    /// aspect, so only its center is checked, the editor keeps the corners inside.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let coords = [self.x1, self.y1, self.x2, self.y2];
+        if !self.is_rotated() && coords.iter().any(|val| !(0.0..=1.0).contains(val)) {
+            bail!("{:?}: coords out of [0, 1] range: {:?}", self.name, coords);
+        }
+        if self.x1 > self.x2 || self.y1 > self.y2 {
+            bail!("{:?}: x1 > x2 or y1 > y2: {:?}", self.name, coords);
+        }
+        let center = self.get_center();
+        if self.is_rotated() && center.iter().any(|val| !(0.0..=1.0).contains(val)) {
+            bail!("{:?}: center out of [0, 1] range: {:?}", self.name, center);
+        }
+        if self.class().trim().is_empty() {
+            bail!("empty name at {:?}", coords);
+        }
+        Ok(())
+    }
+}
+
+/// Gives rois without an id, or with one already taken, the next free id.
+pub fn assign_ids(config: &mut [JsonConfig]) {
+    let mut next_id = config.iter().map(|roi| roi.id).max().unwrap_or(0) + 1;
+    let mut taken = HashSet::new();
+    for roi in config.iter_mut() {
+        if roi.id == 0 || !taken.insert(roi.id) {
+            roi.id = next_id;
+            taken.insert(next_id);
+            next_id += 1;
+        }
+    }
+}
+/// Rotated rois replaced by their bounding boxes, for formats without an angle.
+/// The image size in the roi `frame` is only read when some roi is rotated.
+pub fn bounding_boxes(
+    config: &[JsonConfig],
+    img_path: &Path,
+    frame: RoiFrame,
+) -> anyhow::Result<Vec<JsonConfig>> {
+    if !config.iter().any(JsonConfig::is_rotated) {
+        return Ok(config.to_vec());
+    }
+    let img_size = image_size(img_path, frame)?.map(|val| val as f64);
+    Ok(config
+        .iter()
+        .map(|roi| roi.bounding_box(img_size))
+        .collect())
+}
+
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Json config: rois with the image they belong to. Older configs are a bare roi array,
+/// they are read as is and upgraded on the next write.
+#[derive(Serialize, Deserialize)]
+pub struct ConfigFile {
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<ConfigImage>,
+    /// UTC timestamps like `2024-05-01T12:30:00Z`.
+    #[serde(default)]
+    pub created: String,
+    #[serde(default)]
+    pub modified: String,
+    pub rois: Vec<JsonConfig>,
+}
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ConfigImage {
+    /// Relative to the config directory, with `/` separators.
+    pub path: String,
+    /// `fnv1a64:<hex>` of the image file contents.
+    pub hash: String,
+    /// Pixel size of the image file.
+    pub width: usize,
+    pub height: usize,
+}
+impl ConfigImage {
+    pub fn new(img_path: &Path, fingerprint: ImageFingerprint, config_dir: &Path) -> Self {
+        // the parent of a bare file name is empty, which can't be made absolute
+        let config_dir = match config_dir.as_os_str().is_empty() {
+            true => Path::new("."),
+            false => config_dir,
+        };
+        let config_dir = std::path::absolute(config_dir).unwrap_or(config_dir.to_path_buf());
+        let img_path = std::path::absolute(img_path).unwrap_or(img_path.to_path_buf());
+        Self {
+            path: relative_path(&img_path, &config_dir),
+            hash: fingerprint.hash,
+            width: fingerprint.width,
+            height: fingerprint.height,
+        }
+    }
+    pub fn from_image(img_path: &Path, config_dir: &Path) -> anyhow::Result<Self> {
+        let fingerprint = ImageFingerprint::read(img_path)?;
+        Ok(Self::new(img_path, fingerprint, config_dir))
+    }
+}
+/// Parts of a [`ConfigImage`] that need reading the whole image file.
+#[derive(Clone)]
+pub struct ImageFingerprint {
+    pub hash: String,
+    pub width: usize,
+    pub height: usize,
+}
+impl ImageFingerprint {
+    /// Fingerprint of the image file `bytes` holding pixels of `[width, height]`.
+    pub fn new(bytes: &[u8], [width, height]: [u32; 2]) -> Self {
+        Self {
+            hash: format!("fnv1a64:{:016x}", fnv1a64(bytes)),
+            width: width as usize,
+            height: height as usize,
+        }
+    }
+    pub fn read(img_path: &Path) -> anyhow::Result<Self> {
+        let bytes =
+            fs::read(img_path).with_context(|| format!("reading {}", img_path.display()))?;
+        let (width, height) = ImageReader::new(Cursor::new(&bytes))
+            .with_guessed_format()?
+            .into_dimensions()
+            .with_context(|| format!("reading size of {}", img_path.display()))?;
+        Ok(Self::new(&bytes, [width, height]))
+    }
+}
+
+/// Reads rois from a json config or, for `.txt` files, from YOLO labels.
+pub fn read_config(path: &Path) -> anyhow::Result<Vec<JsonConfig>> {
+    if is_yolo_path(path) {
+        return read_yolo(path);
+    }
+    read_config_file(path).map(|config_file| config_file.rois)
+}
+/// Reads a json config, either versioned or a legacy roi array.
+pub fn read_config_file(path: &Path) -> anyhow::Result<ConfigFile> {
+    let json_string =
+        fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    // parsed into the expected form directly, to keep line and column in errors
+    if json_string.trim_start().starts_with('[') {
+        let rois = serde_json::from_str(&json_string)
+            .with_context(|| format!("parsing {}", path.display()))?;
+        return Ok(ConfigFile {
+            version: 0,
+            image: None,
+            created: String::new(),
+            modified: String::new(),
+            rois,
+        });
+    }
+    let config_file: ConfigFile = serde_json::from_str(&json_string)
+        .with_context(|| format!("parsing {}", path.display()))?;
+    if config_file.version > CONFIG_VERSION {
+        bail!(
+            "{} has version {}, only up to {} is supported",
+            path.display(),
+            config_file.version,
+            CONFIG_VERSION
+        );
+    }
+    Ok(config_file)
+}
+/// Writes a versioned json config, or YOLO labels for `.txt` files.
+/// The image is recorded when given and readable, otherwise the one already recorded is kept,
+/// as is the creation time.
+pub fn write_config(
+    path: &Path,
+    config: &[JsonConfig],
+    img_path: Option<&Path>,
+) -> anyhow::Result<()> {
+    if is_yolo_path(path) {
+        return write_yolo(path, config);
+    }
+    let config_dir = path.parent().unwrap_or(Path::new(""));
+    let image = img_path.and_then(|img_path| ConfigImage::from_image(img_path, config_dir).ok());
+    write_json_config(path, config, image)
+}
+/// Writes a versioned json config with the image when given,
+/// otherwise keeping the one already recorded.
+pub fn write_json_config(
+    path: &Path,
+    config: &[JsonConfig],
+    image: Option<ConfigImage>,
+) -> anyhow::Result<()> {
+    let existing = read_config_file(path).ok();
+    let image = image.or_else(|| {
+        existing
+            .as_ref()
+            .and_then(|existing| existing.image.clone())
+    });
+    let now = format_timestamp(SystemTime::now());
+    let created = existing
+        .map(|existing| existing.created)
+        .filter(|created| !created.is_empty())
+        .unwrap_or_else(|| now.clone());
+    let config_file = ConfigFile {
+        version: CONFIG_VERSION,
+        image,
+        created,
+        modified: now,
+        rois: config.to_vec(),
+    };
+    let json_string = serde_json::to_string_pretty(&config_file)?;
+    fs::write(path, json_string).with_context(|| format!("writing {}", path.display()))
+}
+/// Writes a json config as a bare roi array, for tools that don't know the versioned form.
+pub fn write_legacy_config(path: &Path, config: &[JsonConfig]) -> anyhow::Result<()> {
+    if is_yolo_path(path) {
+        return write_yolo(path, config);
+    }
+    let json_string = serde_json::to_string_pretty(config)?;
+    fs::write(path, json_string).with_context(|| format!("writing {}", path.display()))
+}
+/// The part of a versioned json config read for pairing, the rois are skipped unparsed.
+#[derive(Deserialize)]
+struct ConfigHeader {
+    #[serde(default)]
+    image: Option<ConfigImage>,
+}
+/// Image recorded in a versioned json config.
+pub fn recorded_image(config_path: &Path) -> Option<PathBuf> {
+    if config_path.extension().is_none_or(|ext| ext != "json") {
+        return None;
+    }
+    let file = fs::File::open(config_path).ok()?;
+    let header: ConfigHeader = serde_json::from_reader(BufReader::new(file)).ok()?;
+    Some(config_path.parent()?.join(header.image?.path))
+}
+
+fn fnv1a64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    // days to civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (secs / 86400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+pub fn is_yolo_path(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "txt")
+}
+
+/// First `roi_<image stem>_(N).json` path next to the image, which neither exists nor is `taken`.
+pub fn next_free_config_path(img_path: &Path, taken: &[PathBuf]) -> Option<PathBuf> {
+    let str_name = img_path.file_stem()?.to_string_lossy();
+    (1usize..=100usize)
+        .map(|i| img_path.with_file_name(format!("roi_{}_({}).json", str_name, i)))
+        .find(|new_path| !new_path.exists() && !taken.contains(new_path))
+}
+/// Checks whether the config follows the `roi_<image stem>_(N).json` naming next to the image.
+pub fn is_config_of_image(config_path: &Path, img_path: &Path) -> bool {
+    let (Some(config_name), Some(img_stem)) = (config_path.file_name(), img_path.file_stem())
+    else {
+        return false;
+    };
+    config_path.parent() == img_path.parent()
+        && config_name
+            .to_string_lossy()
+            .starts_with(&format!("roi_{}_(", img_stem.to_string_lossy()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{test_dir, write_png};
+
+    fn fingerprint() -> ImageFingerprint {
+        ImageFingerprint {
+            hash: String::from("fnv1a64:0000000000000000"),
+            width: 4,
+            height: 2,
+        }
+    }
+
+    /// Roi of `[width, height]` pixels around `center` in an image of `img_size`.
+    fn roi_at(center: [f64; 2], size: [f64; 2], img_size: [f64; 2], angle: f64) -> JsonConfig {
+        let rel = |val: f64, half: f64, dim: f64| [(val - half) / dim, (val + half) / dim];
+        let [x1, x2] = rel(center[0], size[0] / 2.0, img_size[0]);
+        let [y1, y2] = rel(center[1], size[1] / 2.0, img_size[1]);
+        JsonConfig {
+            x1,
+            y1,
+            x2,
+            y2,
+            name: String::from("cat"),
+            angle,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rotated_roi_may_exceed_the_image_while_unrotated() {
+        // 150x20 turned upright in a 100x200 image
+        let roi = roi_at([50.0, 100.0], [150.0, 20.0], [100.0, 200.0], 90.0);
+        assert!(roi.x1 < 0.0);
+        assert!(roi.is_inside([100.0, 200.0]));
+        assert!(roi.validate().is_ok());
+        assert!(roi_at([50.0, 100.0], [150.0, 20.0], [100.0, 200.0], 0.0)
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn rotated_corners_leave_the_image() {
+        let roi = roi_at([100.0, 50.0], [120.0, 40.0], [200.0, 100.0], 90.0);
+        assert!(!roi.is_inside([200.0, 100.0]));
+        let roi = roi_at([100.0, 50.0], [80.0, 40.0], [200.0, 100.0], 90.0);
+        assert!(roi.is_inside([200.0, 100.0]));
+        let [x1, y1, x2, y2] = roi.envelope([200.0, 100.0]);
+        assert!((x1 - 0.4).abs() < 1e-9 && (x2 - 0.6).abs() < 1e-9);
+        assert!((y1 - 0.1).abs() < 1e-9 && (y2 - 0.9).abs() < 1e-9);
+    }
+
+    #[test]
+    fn config_image_path_is_relative_for_bare_and_dotted_config_paths() {
+        for config_path in ["roi_a_(1).json", "./roi_a_(1).json"] {
+            let config_dir = Path::new(config_path).parent().unwrap();
+            let image = ConfigImage::new(Path::new("a.png"), fingerprint(), config_dir);
+            assert_eq!(image.path, "a.png", "config at {:?}", config_path);
+        }
+    }
+
+    #[test]
+    fn legacy_configs_are_upgraded_on_write() {
+        let dir = test_dir("config_upgrade");
+        let img_path = dir.join("a.png");
+        write_png(&img_path, [4, 2], 1);
+        let config_path = dir.join("roi_a_(1).json");
+        let roi = roi_at([1.0, 1.0], [2.0, 2.0], [4.0, 2.0], 0.0);
+        write_legacy_config(&config_path, std::slice::from_ref(&roi)).unwrap();
+
+        let legacy = read_config_file(&config_path).unwrap();
+        assert_eq!(legacy.version, 0);
+        assert!(legacy.image.is_none() && legacy.created.is_empty());
+        assert!(legacy.rois == [roi.clone()]);
+
+        write_config(&config_path, &legacy.rois, Some(&img_path)).unwrap();
+        let upgraded = read_config_file(&config_path).unwrap();
+        assert_eq!(upgraded.version, CONFIG_VERSION);
+        let image = upgraded.image.unwrap();
+        assert_eq!(image.path, "a.png");
+        assert_eq!([image.width, image.height], [4, 2]);
+        assert!(image.hash.starts_with("fnv1a64:"));
+        assert!(!upgraded.created.is_empty());
+        assert!(upgraded.rois == [roi]);
+        assert_eq!(
+            recorded_image(&config_path).map(|path| fs::canonicalize(path).unwrap()),
+            Some(fs::canonicalize(&img_path).unwrap())
+        );
+
+        // the image and creation time stay when rewritten without an image
+        write_config(&config_path, &[], None).unwrap();
+        let rewritten = read_config_file(&config_path).unwrap();
+        assert_eq!(rewritten.image.unwrap().hash, image.hash);
+        assert_eq!(rewritten.created, upgraded.created);
+        assert!(rewritten.rois.is_empty());
+    }
+
+    #[test]
+    fn newer_config_versions_are_rejected() {
+        let dir = test_dir("config_version");
+        let config_path = dir.join("roi_a_(1).json");
+        fs::write(&config_path, r#"{"version": 2, "rois": []}"#).unwrap();
+        assert!(read_config_file(&config_path).is_err());
+        fs::write(&config_path, r#"{"version": 1, "rois": []}"#).unwrap();
+        assert!(read_config_file(&config_path).unwrap().created.is_empty());
+    }
+
+    #[test]
+    fn timestamps_are_utc_dates() {
+        let time = |secs| UNIX_EPOCH + std::time::Duration::from_secs(secs);
+        assert_eq!(format_timestamp(time(0)), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(time(951_782_400)), "2000-02-29T00:00:00Z");
+        assert_eq!(
+            format_timestamp(time(1_714_566_600)),
+            "2024-05-01T12:30:00Z"
+        );
+    }
+
+    #[test]
+    fn assign_ids_fills_missing_and_duplicate_ids() {
+        let roi = |id| JsonConfig {
+            id,
+            ..Default::default()
+        };
+        let mut config = [roi(0), roi(3), roi(3), roi(1)];
+        assign_ids(&mut config);
+        let ids: Vec<u64> = config.iter().map(|roi| roi.id).collect();
+        assert_eq!(ids, [4, 3, 5, 1]);
+    }
+
+    #[test]
+    fn corners_turn_clockwise_about_the_center() {
+        // 40x20 around the center of a 100x100 image, turned upright
+        let roi = roi_at([50.0, 50.0], [40.0, 20.0], [100.0, 100.0], 90.0);
+        let corners = roi.corners([100.0, 100.0]);
+        let expected = [[60.0, 30.0], [60.0, 70.0], [40.0, 70.0], [40.0, 30.0]];
+        for (corner, expected) in corners.iter().zip(expected) {
+            assert!((corner[0] - expected[0]).abs() < 1e-9);
+            assert!((corner[1] - expected[1]).abs() < 1e-9);
+        }
+
+        assert!(roi.contains([0.55, 0.35], [100.0, 100.0]));
+        assert!(!roi.contains([0.68, 0.5], [100.0, 100.0]));
+        let unrotated = JsonConfig { angle: 0.0, ..roi };
+        assert!(unrotated.contains([0.68, 0.5], [100.0, 100.0]));
+    }
+
+    #[test]
+    fn bounding_box_encloses_the_corners_within_the_image() {
+        let roi = roi_at([50.0, 50.0], [40.0, 20.0], [100.0, 100.0], 45.0);
+        let bbox = roi.bounding_box([100.0, 100.0]);
+        let half = 30.0 / 2f64.sqrt() / 100.0;
+        assert!((bbox.x1 - (0.5 - half)).abs() < 1e-9 && (bbox.x2 - (0.5 + half)).abs() < 1e-9);
+        assert!((bbox.y1 - (0.5 - half)).abs() < 1e-9 && (bbox.y2 - (0.5 + half)).abs() < 1e-9);
+        assert_eq!(bbox.angle, 0.0);
+        assert_eq!(bbox.name, "cat");
+
+        let roi = roi_at([10.0, 50.0], [40.0, 20.0], [100.0, 100.0], 45.0);
+        assert_eq!(roi.bounding_box([100.0, 100.0]).x1, 0.0);
+    }
 }
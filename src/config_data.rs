@@ -1,4 +1,5 @@
-use crate::config::JsonConfig;
+use crate::config::{assign_ids, JsonConfig};
+use crate::history::History;
 use std::cmp::Ordering;
 
 #[derive(Default)]
@@ -6,15 +7,24 @@ pub struct ConfigData {
     pub config: Vec<JsonConfig>,
     pub edit_idx: Option<usize>,
     pub edit_coord: EditCoord,
+    pub draw_mode: bool,
+    pub draw_start: Option<[f64; 2]>,
+    pub history: History,
+    /// Config as it was last loaded or saved, used to detect unsaved changes.
+    saved: Vec<JsonConfig>,
 }
 impl ConfigData {
-    pub fn find_relevant_roi_at_coord(&self, x: f64, y: f64) -> Option<usize> {
+    /// Roi under the relative point with the closest center, for an image of `[width, height]`.
+    pub fn find_relevant_roi_at_coord(&self, x: f64, y: f64, img_size: [f64; 2]) -> Option<usize> {
         let mut best_match_idx: Option<usize> = None;
         let mut best_center_dist = f64::MAX;
 
-        for (idx, config) in self.config.iter().enumerate().filter(|(_idx, config)| {
-            (config.x1..=config.x2).contains(&x) && (config.y1..config.y2).contains(&y)
-        }) {
+        for (idx, config) in self
+            .config
+            .iter()
+            .enumerate()
+            .filter(|(_idx, config)| config.contains([x, y], img_size))
+        {
             let [cx, cy] = config.get_center();
             let dist = (cx - x).powi(2) + (cy - y).powi(2);
             if dist < best_center_dist {
@@ -24,10 +34,16 @@ impl ConfigData {
         }
         best_match_idx
     }
+    pub fn add_roi(&mut self, roi: JsonConfig) {
+        self.history.record(&self.config);
+        self.config.push(roi);
+        assign_ids(&mut self.config);
+    }
     pub fn safely_remove_roi(&mut self, idx: usize) {
         if idx >= self.config.len() {
             return;
         }
+        self.history.record(&self.config);
         self.config.remove(idx);
         if let Some(edit_idx) = self.edit_idx {
             match idx.cmp(&edit_idx) {
@@ -43,16 +59,86 @@ impl ConfigData {
             }
         }
     }
+    /// Replaces the whole config, e.g. when another config file is opened.
+    /// Rois without an id get one, the file has them after the next save.
+    pub fn reset_config(&mut self, mut config: Vec<JsonConfig>) {
+        assign_ids(&mut config);
+        self.saved = config.clone();
+        self.config = config;
+        self.edit_idx = None;
+        self.edit_coord = EditCoord::None;
+        self.history.clear();
+    }
+    pub fn is_dirty(&self) -> bool {
+        self.config != self.saved
+    }
+    pub fn mark_saved(&mut self) {
+        self.saved = self.config.clone();
+    }
+    /// Reverts the config to its last loaded or saved state.
+    pub fn discard_changes(&mut self) {
+        if self.is_dirty() {
+            self.reset_config(self.saved.clone());
+        }
+    }
+    /// Aborts drawing or dragging, restoring the roi, and deselects it.
+    pub fn cancel_edit(&mut self) {
+        if let Some(state) = self.history.cancel() {
+            self.config = state;
+        }
+        self.draw_start = None;
+        self.edit_idx = None;
+        self.edit_coord = EditCoord::None;
+    }
+    pub fn undo(&mut self) {
+        if self.history.undo(&mut self.config) {
+            self.fix_edit_state();
+        }
+    }
+    pub fn redo(&mut self) {
+        if self.history.redo(&mut self.config) {
+            self.fix_edit_state();
+        }
+    }
+    fn fix_edit_state(&mut self) {
+        self.edit_coord = EditCoord::None;
+        if self.edit_idx.is_some_and(|idx| idx >= self.config.len()) {
+            self.edit_idx = None;
+        }
+    }
 }
+#[derive(Default, Clone, Copy)]
 pub enum EditCoord {
     X1,
     Y1,
     X2,
     Y2,
+    X1Y1,
+    X2Y1,
+    X2Y2,
+    X1Y2,
+    /// Turn the roi around its center.
+    Rotate,
+    /// Translate the whole roi, keeping its size.
+    /// `grab` is the plot position where dragging started, `origin` is the roi at that moment.
+    Body {
+        grab: [f64; 2],
+        origin: [f64; 4],
+    },
+    #[default]
     None,
 }
-impl Default for EditCoord {
-    fn default() -> Self {
-        Self::None
+impl EditCoord {
+    pub fn moves_x1(&self) -> bool {
+        matches!(self, Self::X1 | Self::X1Y1 | Self::X1Y2 | Self::Body { .. })
+    }
+    pub fn moves_y1(&self) -> bool {
+        matches!(self, Self::Y1 | Self::X1Y1 | Self::X2Y1 | Self::Body { .. })
+    }
+    pub fn moves_x2(&self) -> bool {
+        matches!(self, Self::X2 | Self::X2Y1 | Self::X2Y2 | Self::Body { .. })
+    }
+    pub fn moves_y2(&self) -> bool {
+        matches!(self, Self::Y2 | Self::X2Y2 | Self::X1Y2 | Self::Body { .. })
     }
 }
@@ -0,0 +1,242 @@
+use crate::app::RoIApp;
+use crate::config::JsonConfig;
+use crate::orientation::{open_oriented, orient_config, RoiFrame};
+use anyhow::Context;
+use clap::ValueEnum;
+use egui::{ComboBox, DragValue, Window};
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_CROP_TEMPLATE: &str = "{dir}/{image}_crops";
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Copy, ValueEnum)]
+pub enum CropFormat {
+    #[default]
+    Png,
+    Jpeg,
+    /// Keeps 16-bit and float samples as they are.
+    Tiff,
+}
+impl CropFormat {
+    pub const ALL: [Self; 3] = [Self::Png, Self::Jpeg, Self::Tiff];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::Tiff => "tif",
+        }
+    }
+    fn image_format(&self) -> ImageFormat {
+        match self {
+            Self::Png => ImageFormat::Png,
+            Self::Jpeg => ImageFormat::Jpeg,
+            Self::Tiff => ImageFormat::Tiff,
+        }
+    }
+    /// Converts a crop to the closest pixel type the format can store.
+    fn encodable(&self, crop: DynamicImage) -> DynamicImage {
+        match (self, crop) {
+            // jpeg has no alpha channel and only 8 bits
+            (Self::Jpeg, crop) => crop.to_rgb8().into(),
+            (Self::Png, crop @ (DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_))) => {
+                crop.to_rgba16().into()
+            }
+            (Self::Tiff, crop @ DynamicImage::ImageLumaA8(_)) => crop.to_rgba8().into(),
+            (Self::Tiff, crop @ DynamicImage::ImageLumaA16(_)) => crop.to_rgba16().into(),
+            (_, crop) => crop,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CropOptions {
+    /// Pixels added on every side of a roi, limited by the image borders.
+    pub padding: u32,
+    /// Resize every crop to exactly `[width, height]`.
+    pub resize: Option<[u32; 2]>,
+    pub format: CropFormat,
+    /// Output directory with placeholders: `{dir}` image directory,
+    /// `{image}` image file stem and `{name}` roi name.
+    pub template: String,
+    /// Frame the rois are given in. Crops are always saved upright.
+    /// Cropping from the editor always uses the oriented frame of its rois.
+    pub frame: RoiFrame,
+}
+impl Default for CropOptions {
+    fn default() -> Self {
+        Self {
+            padding: 0,
+            resize: None,
+            format: Default::default(),
+            template: DEFAULT_CROP_TEMPLATE.to_string(),
+            frame: Default::default(),
+        }
+    }
+}
+
+/// Makes a roi name usable as a file name.
+fn sanitize_file_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    match sanitized.trim() {
+        "" => String::from("roi"),
+        trimmed => trimmed.to_string(),
+    }
+}
+fn expand_template(template: &str, img_path: &Path, name: &str) -> PathBuf {
+    let dir = match img_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_string_lossy(),
+        _ => ".".into(),
+    };
+    let stem = img_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    PathBuf::from(
+        template
+            .replace("{dir}", &dir)
+            .replace("{image}", &stem)
+            .replace("{name}", name),
+    )
+}
+
+/// Crops every roi at full resolution and saves it as `<template dir>/<roi name>.<ext>`,
+/// keeping the original bit depth where the format allows.
+/// Rois with equal names get a numeric suffix instead of overwriting each other.
+/// Returns paths of the written files.
+pub fn crop_images(
+    items: &[(PathBuf, Vec<JsonConfig>)],
+    options: &CropOptions,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let mut written: Vec<PathBuf> = Vec::new();
+    for (img_path, config) in items.iter().filter(|(_, config)| !config.is_empty()) {
+        let (img, orientation) =
+            open_oriented(img_path).with_context(|| format!("opening {}", img_path.display()))?;
+        let config = match options.frame {
+            RoiFrame::Oriented => config.to_vec(),
+            RoiFrame::Raw => orient_config(config, orientation),
+        };
+
+        let img_size = [img.width() as f64, img.height() as f64];
+        for roi in &config {
+            // rotated rois are cropped with their bounding box
+            let [x1, y1, x2, y2] = roi
+                .bounding_box(img_size)
+                .get_abs_pixel_coords(img_size[0], img_size[1])
+                .map(|val| val as u32);
+            let x1 = x1.saturating_sub(options.padding);
+            let y1 = y1.saturating_sub(options.padding);
+            let x2 = x2.saturating_add(options.padding).min(img.width());
+            let y2 = y2.saturating_add(options.padding).min(img.height());
+            if x2 <= x1 || y2 <= y1 {
+                continue;
+            }
+
+            let name = sanitize_file_name(&roi.name);
+            let out_dir = expand_template(&options.template, img_path, &name);
+            fs::create_dir_all(&out_dir)
+                .with_context(|| format!("creating {}", out_dir.display()))?;
+            let ext = options.format.extension();
+            let mut out_path = out_dir.join(format!("{}.{}", name, ext));
+            let mut idx = 1usize;
+            while written.contains(&out_path) {
+                idx += 1;
+                out_path = out_dir.join(format!("{}_{}.{}", name, idx, ext));
+            }
+
+            let mut crop = img.crop_imm(x1, y1, x2 - x1, y2 - y1);
+            if let Some([width, height]) = options.resize {
+                crop = crop.resize_exact(width, height, FilterType::Lanczos3);
+            }
+            options
+                .format
+                .encodable(crop)
+                .save_with_format(&out_path, options.format.image_format())
+                .with_context(|| format!("writing {}", out_path.display()))?;
+            written.push(out_path);
+        }
+    }
+    Ok(written)
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct CropSettings {
+    #[serde(skip)]
+    pub open: bool,
+    pub options: CropOptions,
+}
+
+impl RoIApp {
+    pub fn render_crop_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.crop_settings.open;
+        let mut crop_clicked = false;
+        Window::new("Export crops")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let options = &mut self.crop_settings.options;
+                ui.horizontal(|ui| {
+                    ui.label("padding:");
+                    ui.add(DragValue::new(&mut options.padding).suffix(" px"));
+                });
+                ui.horizontal(|ui| {
+                    let mut resize = options.resize.is_some();
+                    ui.checkbox(&mut resize, "resize to");
+                    let [mut width, mut height] = options.resize.unwrap_or([224, 224]);
+                    ui.add_enabled(resize, DragValue::new(&mut width).range(1..=u16::MAX));
+                    ui.label("x");
+                    ui.add_enabled(resize, DragValue::new(&mut height).range(1..=u16::MAX));
+                    options.resize = resize.then_some([width, height]);
+                });
+                ComboBox::from_label("format")
+                    .selected_text(options.format.extension())
+                    .show_ui(ui, |ui| {
+                        for format in CropFormat::ALL {
+                            ui.selectable_value(&mut options.format, format, format.extension());
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    ui.label("output dir:");
+                    ui.text_edit_singleline(&mut options.template);
+                })
+                .response
+                .on_hover_text("{dir} - image directory, {image} - image name, {name} - roi name");
+
+                let can_crop = self.selected_img.is_some() && !self.config_data.config.is_empty();
+                crop_clicked = ui
+                    .add_enabled(can_crop, egui::Button::new("Crop current config"))
+                    .clicked();
+            });
+        self.crop_settings.open = open;
+
+        if crop_clicked {
+            if let Some(img_path) = &self.selected_img {
+                let items = [(img_path.to_path_buf(), self.config_data.config.clone())];
+                // the editor holds rois of the oriented image, whatever the files use
+                let options = CropOptions {
+                    frame: RoiFrame::Oriented,
+                    ..self.crop_settings.options.clone()
+                };
+                match crop_images(&items, &options) {
+                    Ok(written) => self
+                        .notifications
+                        .info(format!("Wrote {} crops", written.len())),
+                    Err(err) => self.notifications.error(format!("Crop failed: {:#}", err)),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::coco::{import_coco, looks_like_coco};
+use crate::config::{read_config, JsonConfig};
+use crate::orientation::{image_size, RoiFrame};
+use crate::pairing::{find_image_of_config, ConfigImages};
+use crate::project::expand_projects;
+use crate::voc::import_voc;
+use crate::yolo::CLASSES_FILE_NAME;
+use anyhow::bail;
+use std::path::{Path, PathBuf};
+
+pub const IMAGE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "tif", "tiff", "bmp", "webp"];
+
+pub fn is_image_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Finds the image of a YOLO label among `imgs_paths` by file stem,
+/// then next to the label or in a sibling `images` dir.
+fn find_yolo_image(label_path: &Path, imgs_paths: &[PathBuf]) -> Option<PathBuf> {
+    let stem = label_path.file_stem()?;
+    if let Some(img_path) = imgs_paths
+        .iter()
+        .find(|img_path| img_path.file_stem() == Some(stem))
+    {
+        return Some(img_path.to_path_buf());
+    }
+    let dir = label_path.parent()?;
+    let dirs = [
+        Some(dir.to_path_buf()),
+        dir.parent().map(|d| d.join("images")),
+    ];
+    dirs.into_iter().flatten().find_map(|dir| {
+        IMAGE_EXTENSIONS
+            .iter()
+            .map(|ext| dir.join(format!("{}.{}", stem.to_string_lossy(), ext)))
+            .find(|img_path| img_path.is_file())
+    })
+}
+
+/// Loads images with their rois from a mix of image files, RoI json configs,
+/// COCO json, YOLO txt labels, Pascal VOC xml files and RoI projects.
+/// Sizes read from images are in the `frame` the rois refer to.
+/// Rois of all annotations of the same image are merged. RoI json configs without a given image
+/// fall back to the image they record or are named after.
+pub fn load_items(
+    inputs: &[PathBuf],
+    frame: RoiFrame,
+) -> anyhow::Result<Vec<(PathBuf, Vec<JsonConfig>)>> {
+    let inputs = &expand_projects(inputs)?;
+    // for annotations without image sizes
+    let size_of = |img_path: &Path| {
+        image_size(img_path, frame)
+            .ok()
+            .map(|size| size.map(|val| val as usize))
+    };
+    let imgs_paths: Vec<PathBuf> = inputs
+        .iter()
+        .filter(|path| is_image_path(path))
+        .cloned()
+        .collect();
+    let mut items: Vec<(PathBuf, Vec<JsonConfig>)> = imgs_paths
+        .iter()
+        .map(|img_path| (img_path.to_path_buf(), Vec::new()))
+        .collect();
+    let mut config_images = ConfigImages::default();
+    let mut add = |img_path: PathBuf, config: Vec<JsonConfig>| match items
+        .iter_mut()
+        .find(|(path, _)| path == &img_path)
+    {
+        Some((_, rois)) => rois.extend(config),
+        None => items.push((img_path, config)),
+    };
+
+    for path in inputs.iter().filter(|path| !is_image_path(path)) {
+        let ext = path.extension().and_then(|e| e.to_str());
+        match ext {
+            Some("json") if looks_like_coco(path) => {
+                for (img_path, config) in import_coco(path, size_of)? {
+                    add(img_path, config);
+                }
+            }
+            Some("json") => {
+                let config = read_config(path)?;
+                let Some(img_path) = imgs_paths
+                    .iter()
+                    .find(|img_path| config_images.belongs(path, img_path))
+                    .cloned()
+                    .or_else(|| find_image_of_config(path))
+                else {
+                    bail!("no image found for {}", path.display());
+                };
+                add(img_path, config);
+            }
+            Some("txt") if path.file_name().is_some_and(|n| n != CLASSES_FILE_NAME) => {
+                let config = read_config(path)?;
+                let Some(img_path) = find_yolo_image(path, &imgs_paths) else {
+                    bail!("no image found for {}", path.display());
+                };
+                add(img_path, config);
+            }
+            Some("xml") => {
+                let (img_path, config) = import_voc(path, size_of)?;
+                add(img_path, config);
+            }
+            _ => bail!("unsupported file {}", path.display()),
+        }
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::write_config;
+    use crate::test_util::{test_dir, write_png};
+
+    #[test]
+    fn config_without_given_image_loads_its_recorded_image() {
+        let dir = test_dir("recorded_image");
+        let img_path = dir.join("img.png");
+        write_png(&img_path, [4, 2], 1);
+        let config_path = dir.join("out").join("cats.json");
+        std::fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+        let config = vec![JsonConfig {
+            x2: 0.5,
+            y2: 0.5,
+            name: String::from("cat"),
+            ..Default::default()
+        }];
+        write_config(&config_path, &config, Some(&img_path)).unwrap();
+
+        let items = load_items(&[config_path], RoiFrame::Oriented).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(
+            std::fs::canonicalize(&items[0].0).unwrap(),
+            std::fs::canonicalize(&img_path).unwrap()
+        );
+        assert_eq!(items[0].1[0].name, "cat");
+    }
+
+    #[test]
+    fn malformed_configs_report_their_own_error() {
+        let dir = test_dir("malformed_config");
+        let config_path = dir.join("roi_a_(1).json");
+        std::fs::write(&config_path, r#"[{"x1": 0.1}]"#).unwrap();
+        let Err(err) = load_items(&[config_path], RoiFrame::Oriented) else {
+            panic!("expected an error for a malformed config");
+        };
+        let err = format!("{:#}", err);
+        assert!(err.contains("missing field"), "{}", err);
+    }
+}
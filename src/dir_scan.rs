@@ -0,0 +1,327 @@
+use crate::app::RoIApp;
+use crate::coco::looks_like_coco;
+use crate::config::read_config;
+use crate::dataset::is_image_path;
+use crate::pairing::Sidecars;
+use crate::yolo::CLASSES_FILE_NAME;
+use egui::{DragValue, Window};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanSettings {
+    /// How many directory levels below the dropped one are visited.
+    pub max_depth: usize,
+    /// File and directory names to skip, one pattern per line.
+    /// `*` matches any run of characters.
+    pub ignore: String,
+}
+impl Default for ScanSettings {
+    fn default() -> Self {
+        Self {
+            max_depth: 8,
+            ignore: String::from(".*\nnode_modules\ntarget\n__pycache__"),
+        }
+    }
+}
+impl ScanSettings {
+    pub fn ignore_patterns(&self) -> Vec<String> {
+        self.ignore
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+}
+
+pub enum ScanEntry {
+    Image(PathBuf),
+    /// Config with the canonical path of the image assigned to it, see [`Sidecars::resolve`].
+    Config(PathBuf, Option<PathBuf>),
+    /// Pascal VOC xml or COCO json, imported like a dropped file.
+    Annotations(PathBuf),
+}
+
+/// Recursive directory walk running on a worker thread.
+pub struct DirScan {
+    pub root: PathBuf,
+    pub n_images: usize,
+    pub n_configs: usize,
+    pub n_annotations: usize,
+    receiver: Receiver<ScanEntry>,
+    visited: Arc<AtomicUsize>,
+    cancel: Arc<AtomicBool>,
+    done: bool,
+}
+impl DirScan {
+    pub fn start(root: PathBuf, settings: &ScanSettings) -> Self {
+        let (sender, receiver) = channel();
+        let visited = Arc::new(AtomicUsize::new(0));
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let walk_root = root.to_path_buf();
+        let walk_visited = visited.clone();
+        let walk_cancel = cancel.clone();
+        let max_depth = settings.max_depth;
+        let ignore = settings.ignore_patterns();
+        thread::spawn(move || {
+            walk(
+                &walk_root,
+                max_depth,
+                &ignore,
+                &sender,
+                &walk_visited,
+                &walk_cancel,
+            )
+        });
+
+        Self {
+            root,
+            n_images: 0,
+            n_configs: 0,
+            n_annotations: 0,
+            receiver,
+            visited,
+            cancel,
+            done: false,
+        }
+    }
+    /// Takes everything found since the last call.
+    pub fn poll(&mut self) -> Vec<ScanEntry> {
+        let mut entries = Vec::new();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(entry) => {
+                    match entry {
+                        ScanEntry::Image(_) => self.n_images += 1,
+                        ScanEntry::Config(..) => self.n_configs += 1,
+                        ScanEntry::Annotations(_) => self.n_annotations += 1,
+                    }
+                    entries.push(entry);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.done = true;
+                    break;
+                }
+            }
+        }
+        entries
+    }
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+    pub fn visited(&self) -> usize {
+        self.visited.load(Ordering::Relaxed)
+    }
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Matches a file name against a pattern where `*` stands for any run of characters.
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // no `*` at all
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+fn walk(
+    root: &Path,
+    max_depth: usize,
+    ignore: &[String],
+    sender: &Sender<ScanEntry>,
+    visited: &AtomicUsize,
+    cancel: &AtomicBool,
+) {
+    let mut sidecars = Sidecars::default();
+    let mut stack = vec![(root.to_path_buf(), 0usize)];
+    while let Some((dir, depth)) = stack.pop() {
+        let Ok(read_dir) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut entries: Vec<_> = read_dir.filter_map(|entry| entry.ok()).collect();
+        entries.sort_by_key(|entry| entry.file_name());
+
+        let mut sub_dirs = Vec::new();
+        for entry in entries {
+            if cancel.load(Ordering::Relaxed) {
+                return;
+            }
+            visited.fetch_add(1, Ordering::Relaxed);
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if ignore.iter().any(|pattern| matches_pattern(pattern, &name)) {
+                continue;
+            }
+            // file_type doesn't follow symlinks, so linked dirs can't make loops
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if file_type.is_dir() {
+                if depth < max_depth {
+                    sub_dirs.push((path, depth + 1));
+                }
+                continue;
+            }
+            let found = if is_image_path(&path) {
+                Some(ScanEntry::Image(path))
+            } else if is_config_candidate(&path) && read_config(&path).is_ok() {
+                let assigned = sidecars.resolve(&path);
+                Some(ScanEntry::Config(path, assigned))
+            } else if is_annotations_candidate(&path) {
+                Some(ScanEntry::Annotations(path))
+            } else {
+                None
+            };
+            if let Some(found) = found {
+                if sender.send(found).is_err() {
+                    return;
+                }
+            }
+        }
+        // visit sub dirs in name order
+        stack.extend(sub_dirs.into_iter().rev());
+    }
+}
+fn is_config_candidate(path: &Path) -> bool {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => true,
+        Some("txt") => path
+            .file_name()
+            .is_some_and(|name| name != CLASSES_FILE_NAME),
+        _ => false,
+    }
+}
+
+fn is_annotations_candidate(path: &Path) -> bool {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("xml") => true,
+        Some("json") => looks_like_coco(path),
+        _ => false,
+    }
+}
+
+impl RoIApp {
+    /// Adds what running directory scans found so far and shows their progress.
+    pub fn process_dir_scans(&mut self, ctx: &egui::Context) {
+        if self.dir_scans.is_empty() {
+            return;
+        }
+        for scan_idx in 0..self.dir_scans.len() {
+            let entries = self.dir_scans[scan_idx].poll();
+            if entries.is_empty() {
+                continue;
+            }
+            let mut known: HashSet<PathBuf> = self
+                .imgs_paths
+                .iter()
+                .chain(self.configs_paths.iter())
+                .cloned()
+                .collect();
+            for entry in entries {
+                match entry {
+                    ScanEntry::Image(path) => {
+                        if known.insert(path.to_path_buf()) {
+                            self.imgs_paths.push(path);
+                        }
+                    }
+                    ScanEntry::Config(path, assigned) => {
+                        if known.insert(path.to_path_buf()) {
+                            self.config_images.insert(path.to_path_buf(), assigned);
+                            self.list_config(path);
+                        }
+                    }
+                    ScanEntry::Annotations(path) => self.add_file(&path),
+                }
+            }
+        }
+        self.dir_scans.retain(|scan| !scan.is_done());
+        if self.dir_scans.is_empty() {
+            return;
+        }
+
+        Window::new("Scanning folders")
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                for scan in &self.dir_scans {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(scan.root.to_string_lossy());
+                        if ui.small_button("cancel").clicked() {
+                            scan.cancel();
+                        }
+                    });
+                    ui.label(format!(
+                        "{} files visited, {} images, {} configs, {} annotation files",
+                        scan.visited(),
+                        scan.n_images,
+                        scan.n_configs,
+                        scan.n_annotations
+                    ));
+                }
+            });
+        // keep polling while the workers run
+        ctx.request_repaint();
+    }
+    pub fn render_scan_settings_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.scan_settings_open;
+        Window::new("Folder scan settings")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let settings = &mut self.scan_settings;
+                ui.horizontal(|ui| {
+                    ui.label("max depth:");
+                    ui.add(DragValue::new(&mut settings.max_depth).range(0..=64));
+                });
+                ui.label("ignore, one pattern per line:");
+                ui.text_edit_multiline(&mut settings.ignore);
+            });
+        self.scan_settings_open = open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_match_whole_names_with_wildcards() {
+        assert!(matches_pattern("target", "target"));
+        assert!(!matches_pattern("target", "targets"));
+        assert!(matches_pattern(".*", ".git"));
+        assert!(!matches_pattern(".*", "a.git"));
+        assert!(matches_pattern("*.tmp", "a.tmp"));
+        assert!(matches_pattern("a*b*c", "abc"));
+        assert!(matches_pattern("a*b*c", "a_b_b_c"));
+        assert!(!matches_pattern("a*b*c", "a_c_b"));
+        // the end part must not overlap the start
+        assert!(!matches_pattern("ab*ba", "aba"));
+        assert!(matches_pattern("*", ""));
+    }
+}
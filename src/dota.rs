@@ -0,0 +1,87 @@
+use crate::config::JsonConfig;
+use crate::export::check_unique_stems;
+use crate::orientation::{image_size, RoiFrame};
+use anyhow::Context;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Formats rois as DOTA `x1 y1 x2 y2 x3 y3 x4 y4 class difficult` lines,
+/// with corners in pixels of an image of `[width, height]`.
+pub fn format_dota(config: &[JsonConfig], img_size: [f64; 2]) -> String {
+    let mut text = String::new();
+    for roi in config {
+        for [x, y] in roi.corners(img_size) {
+            text += &format!("{:.1} {:.1} ", x, y);
+        }
+        // DOTA splits lines on whitespace, so class names can't contain any
+        let class: String = roi
+            .class()
+            .chars()
+            .map(|c| if c.is_whitespace() { '_' } else { c })
+            .collect();
+        text += &format!("{} 0\n", class);
+    }
+    text
+}
+
+/// Writes `<image stem>.txt` for every image into `out_dir`,
+/// reading image sizes from the files in the `frame` the rois refer to.
+pub fn export_dota(
+    items: &[(PathBuf, Vec<JsonConfig>)],
+    out_dir: &Path,
+    frame: RoiFrame,
+) -> anyhow::Result<()> {
+    check_unique_stems(items)?;
+    fs::create_dir_all(out_dir).with_context(|| format!("creating {}", out_dir.display()))?;
+    for (img_path, config) in items {
+        let Some(stem) = img_path.file_stem() else {
+            continue;
+        };
+        let [width, height] = image_size(img_path, frame)?;
+        let label_path = out_dir.join(format!("{}.txt", stem.to_string_lossy()));
+        let text = format_dota(config, [width as f64, height as f64]);
+        fs::write(&label_path, text)
+            .with_context(|| format!("writing {}", label_path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{test_dir, write_png};
+
+    #[test]
+    fn export_uses_the_oriented_size() {
+        let dir = test_dir("dota_oriented");
+        let img_path = dir.join("img.png");
+        write_png(&img_path, [4, 2], 6);
+        let roi = JsonConfig {
+            x2: 1.0,
+            y2: 1.0,
+            name: String::from("cat"),
+            ..Default::default()
+        };
+        export_dota(&[(img_path, vec![roi])], &dir, RoiFrame::Oriented).unwrap();
+        let text = fs::read_to_string(dir.join("img.txt")).unwrap();
+        assert_eq!(text, "0.0 0.0 2.0 0.0 2.0 4.0 0.0 4.0 cat 0\n");
+    }
+
+    #[test]
+    fn lines_list_the_turned_corners_and_join_class_words() {
+        let roi = JsonConfig {
+            x1: 0.3,
+            y1: 0.4,
+            x2: 0.7,
+            y2: 0.6,
+            name: String::from("tabby cat"),
+            angle: 90.0,
+            ..Default::default()
+        };
+        let text = format_dota(&[roi], [100.0, 100.0]);
+        assert_eq!(
+            text,
+            "60.0 30.0 60.0 70.0 40.0 70.0 40.0 30.0 tabby_cat 0\n"
+        );
+    }
+}
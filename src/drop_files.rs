@@ -1,40 +1,112 @@
 use crate::app::RoIApp;
-use crate::config::JsonConfig;
-use std::fs::read_to_string;
+use crate::coco::{import_coco, looks_like_coco};
+use crate::config::read_config;
+use crate::dataset::is_image_path;
+use crate::dir_scan::DirScan;
+use crate::orientation::image_size;
+use crate::project::PROJECT_EXTENSION;
+use crate::unsaved_changes::PendingAction;
+use crate::voc::import_voc;
+use crate::yolo::CLASSES_FILE_NAME;
+use std::path::{Path, PathBuf};
 
 impl RoIApp {
     pub fn process_dropped_files(&mut self, ctx: &egui::Context) {
         render_files_being_dropped(ctx);
 
         // Collect dropped files:
-        ctx.input(|i| {
-            if !i.raw.dropped_files.is_empty() {
-                for dropped_file in &i.raw.dropped_files {
-                    if let Some(path) = &dropped_file.path {
-                        let ext = path.extension().and_then(|e| e.to_str());
-                        match ext {
-                            Some("png") | Some("jpg") | Some("jpeg") => {
-                                if !self.imgs_paths.contains(path) {
-                                    self.imgs_paths.push(path.to_path_buf());
-                                }
-                            }
-                            Some("json") => {
-                                if !self.configs_paths.contains(path) {
-                                    if let Ok(json_string) = read_to_string(path) {
-                                        if serde_json::from_str::<Vec<JsonConfig>>(&json_string)
-                                            .is_ok()
-                                        {
-                                            self.configs_paths.push(path.to_path_buf());
-                                        }
-                                    }
-                                }
-                            }
-                            _ => (),
+        let dropped_paths: Vec<PathBuf> = ctx.input(|i| {
+            i.raw
+                .dropped_files
+                .iter()
+                .filter_map(|dropped_file| dropped_file.path.clone())
+                .collect()
+        });
+        for path in dropped_paths {
+            self.add_path(&path);
+        }
+    }
+    /// Adds an image or a config to the lists, imports COCO and VOC annotations,
+    /// opens projects and loads labels from `classes.txt`.
+    /// A directory is scanned recursively in the background.
+    pub fn add_path(&mut self, path: &Path) {
+        if path.is_dir() {
+            self.dir_scans
+                .push(DirScan::start(path.to_path_buf(), &self.scan_settings));
+        } else {
+            self.add_file(path);
+        }
+    }
+    /// Adds a single file like [`RoIApp::add_path`].
+    pub fn add_file(&mut self, path: &Path) {
+        let listed = self
+            .imgs_paths
+            .iter()
+            .chain(&self.configs_paths)
+            .any(|p| p == path);
+        let ext = path.extension().and_then(|e| e.to_str());
+        match ext {
+            _ if listed => (),
+            _ if is_image_path(path) => {
+                self.imgs_paths.push(path.to_path_buf());
+            }
+            Some("txt") if path.file_name().is_some_and(|n| n == CLASSES_FILE_NAME) => {
+                self.load_labels(path);
+            }
+            Some("txt") => match read_config(path) {
+                Ok(_) => self.list_config(path.to_path_buf()),
+                Err(err) => self.notifications.error(format!("{:#}", err)),
+            },
+            Some("json") => match read_config(path) {
+                Ok(_) => self.list_config(path.to_path_buf()),
+                Err(config_err) => match import_coco(path, |img_path| {
+                    image_size(img_path, self.image_settings.roi_frame)
+                        .ok()
+                        .map(|size| size.map(|val| val as usize))
+                }) {
+                    Ok(items) => {
+                        if let Err(err) = self.add_imported(items) {
+                            self.notifications.error(format!(
+                                "Failed importing {}: {:#}",
+                                path.display(),
+                                err
+                            ));
                         }
                     }
+                    // report the error of the format the file was meant to be
+                    Err(coco_err) => {
+                        let err = if looks_like_coco(path) {
+                            coco_err
+                        } else {
+                            config_err
+                        };
+                        self.notifications.error(format!("{:#}", err));
+                    }
+                },
+            },
+            Some(PROJECT_EXTENSION) => {
+                self.request_action(PendingAction::OpenProject(path.to_path_buf()));
+            }
+            Some("xml") => {
+                let frame = self.image_settings.roi_frame;
+                let imported = import_voc(path, |img_path| {
+                    image_size(img_path, frame)
+                        .ok()
+                        .map(|size| size.map(|val| val as usize))
+                })
+                .and_then(|item| self.add_imported(vec![item]));
+                if let Err(err) = imported {
+                    self.notifications.error(format!(
+                        "Failed importing {}: {:#}",
+                        path.display(),
+                        err
+                    ));
                 }
             }
-        })
+            _ => self
+                .notifications
+                .warn(format!("Unsupported file {}", path.display())),
+        }
     }
 }
 
@@ -0,0 +1,217 @@
+use crate::app::RoIApp;
+use crate::coco::export_coco;
+use crate::config::{next_free_config_path, read_config, write_config, JsonConfig};
+use crate::dota::export_dota;
+use crate::orientation::RoiFrame;
+use crate::voc::export_voc;
+use crate::yolo::{export_yolo, export_yolo_obb};
+use anyhow::{bail, Context};
+use clap::ValueEnum;
+use egui::{ComboBox, Window};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    /// Single COCO `instances.json`
+    #[default]
+    Coco,
+    /// Directory with YOLO `.txt` labels and `classes.txt`
+    Yolo,
+    /// Directory with YOLO-OBB `.txt` labels of rotated corners and `classes.txt`
+    YoloObb,
+    /// Directory with DOTA `.txt` labels of rotated corners in pixels
+    Dota,
+    /// Directory with Pascal VOC `.xml` files
+    Voc,
+    /// Directory with `roi_<image stem>_(N).json` configs
+    Json,
+}
+impl ExportFormat {
+    pub const ALL: [Self; 6] = [
+        Self::Coco,
+        Self::Yolo,
+        Self::YoloObb,
+        Self::Dota,
+        Self::Voc,
+        Self::Json,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Coco => "COCO json",
+            Self::Yolo => "YOLO txt",
+            Self::YoloObb => "YOLO-OBB txt",
+            Self::Dota => "DOTA txt",
+            Self::Voc => "Pascal VOC xml",
+            Self::Json => "RoI json configs",
+        }
+    }
+    fn default_file_name(&self) -> &'static str {
+        match self {
+            Self::Coco => "instances.json",
+            Self::Yolo | Self::YoloObb => "labels",
+            Self::Dota => "labelTxt",
+            Self::Voc => "Annotations",
+            Self::Json => "configs",
+        }
+    }
+    /// Writes images with their rois to `out_path`, a file or a directory depending on the format.
+    /// Pixel coords are in the `frame` the rois refer to.
+    pub fn export(
+        &self,
+        items: &[(PathBuf, Vec<JsonConfig>)],
+        out_path: &Path,
+        frame: RoiFrame,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Coco => export_coco(items, out_path, frame),
+            Self::Yolo => export_yolo(items, out_path, frame),
+            Self::YoloObb => export_yolo_obb(items, out_path, frame),
+            Self::Dota => export_dota(items, out_path, frame),
+            Self::Voc => export_voc(items, out_path, frame),
+            Self::Json => export_json(items, out_path),
+        }
+    }
+}
+
+/// Fails when images in different directories share a file stem,
+/// as their `<image stem>.<ext>` label files would overwrite each other.
+pub fn check_unique_stems(items: &[(PathBuf, Vec<JsonConfig>)]) -> anyhow::Result<()> {
+    let mut stems: HashMap<&OsStr, &PathBuf> = HashMap::new();
+    for (img_path, _) in items {
+        let Some(stem) = img_path.file_stem() else {
+            continue;
+        };
+        if let Some(other) = stems.insert(stem, img_path) {
+            if other != img_path {
+                bail!(
+                    "{} and {} would get the same label file, rename one of them",
+                    other.display(),
+                    img_path.display()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes non-empty configs as `roi_<image stem>_(N).json` into `out_dir`.
+fn export_json(items: &[(PathBuf, Vec<JsonConfig>)], out_dir: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(out_dir).with_context(|| format!("creating {}", out_dir.display()))?;
+    let mut written = Vec::new();
+    for (img_path, config) in items.iter().filter(|(_, config)| !config.is_empty()) {
+        let Some(file_name) = img_path.file_name() else {
+            continue;
+        };
+        if let Some(config_path) = next_free_config_path(&out_dir.join(file_name), &written) {
+            write_config(&config_path, config, Some(img_path))?;
+            written.push(config_path);
+        }
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ExportSettings {
+    #[serde(skip)]
+    pub open: bool,
+    pub format: ExportFormat,
+    pub out_path: String,
+}
+
+impl RoIApp {
+    /// Pairs every listed image with the rois of all its configs found on disk.
+    /// Every config is read once, those that fail to load are skipped with a warning.
+    /// The open config is taken as edited, with unsaved changes.
+    pub fn collect_annotated_images(&mut self) -> Vec<(PathBuf, Vec<JsonConfig>)> {
+        let mut loaded: HashMap<&PathBuf, Vec<JsonConfig>> = HashMap::new();
+        if let Some(selected_config) = &self.selected_config {
+            loaded.insert(selected_config, self.config_in_file_frame());
+        }
+        let configs_paths: Vec<&PathBuf> = self
+            .configs_paths
+            .iter()
+            .filter(|config_path| loaded.contains_key(config_path) || config_path.exists())
+            .collect();
+        let mut items = Vec::with_capacity(self.imgs_paths.len());
+        for img_path in &self.imgs_paths {
+            let mut config = Vec::new();
+            for config_path in &configs_paths {
+                if !self.config_images.belongs(config_path, img_path) {
+                    continue;
+                }
+                let rois = loaded.entry(config_path).or_insert_with(|| {
+                    read_config(config_path).unwrap_or_else(|err| {
+                        self.notifications.warn(format!("Skipped {:#}", err));
+                        Vec::new()
+                    })
+                });
+                config.extend(rois.iter().cloned());
+            }
+            items.push((img_path.to_path_buf(), config));
+        }
+        items
+    }
+    pub fn render_export_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.export_settings.open;
+        let mut export_clicked = false;
+        Window::new("Export")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let settings = &mut self.export_settings;
+                let previous_format = settings.format;
+                ComboBox::from_label("format")
+                    .selected_text(settings.format.label())
+                    .show_ui(ui, |ui| {
+                        for format in ExportFormat::ALL {
+                            ui.selectable_value(&mut settings.format, format, format.label());
+                        }
+                    });
+                let imgs_dir = self.imgs_paths.first().and_then(|p| p.parent());
+                let default_out_path = |format: ExportFormat| {
+                    imgs_dir.map(|dir| {
+                        dir.join(format.default_file_name())
+                            .to_string_lossy()
+                            .to_string()
+                    })
+                };
+                // a path typed in by the user stays when switching formats
+                if settings.out_path.is_empty()
+                    || Some(&settings.out_path) == default_out_path(previous_format).as_ref()
+                {
+                    if let Some(out_path) = default_out_path(settings.format) {
+                        settings.out_path = out_path;
+                    }
+                }
+                ui.horizontal(|ui| {
+                    ui.label("output:");
+                    ui.text_edit_singleline(&mut settings.out_path);
+                });
+                ui.label(format!("{} images", self.imgs_paths.len()));
+                export_clicked = ui.button("Export").clicked();
+            });
+        self.export_settings.open = open;
+
+        if export_clicked {
+            let items = self.collect_annotated_images();
+            let out_path = PathBuf::from(&self.export_settings.out_path);
+            let frame = self.image_settings.roi_frame;
+            let result = self.export_settings.format.export(&items, &out_path, frame);
+            match result {
+                Ok(()) => self
+                    .notifications
+                    .info(format!("Exported {}", out_path.display())),
+                Err(err) => self
+                    .notifications
+                    .error(format!("Export failed: {:#}", err)),
+            }
+        }
+    }
+}
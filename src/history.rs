@@ -0,0 +1,69 @@
+use crate::config::JsonConfig;
+
+/// Max number of undo steps kept per config.
+const MAX_HISTORY_LEN: usize = 200;
+
+/// Snapshot-based undo/redo stack for roi edits.
+#[derive(Default)]
+pub struct History {
+    undo_stack: Vec<Vec<JsonConfig>>,
+    redo_stack: Vec<Vec<JsonConfig>>,
+    pending: Option<Vec<JsonConfig>>,
+}
+impl History {
+    /// Records `state` as one undo step. Call right before a single-shot edit.
+    pub fn record(&mut self, state: &[JsonConfig]) {
+        self.finish(state);
+        self.push_undo(state.to_vec());
+    }
+    /// Remembers `state` as the start of a continuous edit, like a drag or typing.
+    pub fn begin(&mut self, state: &[JsonConfig]) {
+        self.finish(state);
+        self.pending = Some(state.to_vec());
+    }
+    /// Turns the continuous edit started with [`History::begin`] into one undo step,
+    /// if it actually changed anything.
+    pub fn finish(&mut self, state: &[JsonConfig]) {
+        if let Some(pending) = self.pending.take() {
+            if pending != state {
+                self.push_undo(pending);
+            }
+        }
+    }
+    /// Drops the continuous edit, returning the state it started from.
+    pub fn cancel(&mut self) -> Option<Vec<JsonConfig>> {
+        self.pending.take()
+    }
+    /// Restores the previous state into `current`. Returns `false` if there is nothing to undo.
+    pub fn undo(&mut self, current: &mut Vec<JsonConfig>) -> bool {
+        self.finish(current);
+        match self.undo_stack.pop() {
+            Some(prev) => {
+                self.redo_stack.push(std::mem::replace(current, prev));
+                true
+            }
+            None => false,
+        }
+    }
+    /// Reapplies the last undone state into `current`. Returns `false` if there is nothing to redo.
+    pub fn redo(&mut self, current: &mut Vec<JsonConfig>) -> bool {
+        self.finish(current);
+        match self.redo_stack.pop() {
+            Some(next) => {
+                self.undo_stack.push(std::mem::replace(current, next));
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn clear(&mut self) {
+        *self = Default::default();
+    }
+    fn push_undo(&mut self, state: Vec<JsonConfig>) {
+        if self.undo_stack.len() >= MAX_HISTORY_LEN {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(state);
+        self.redo_stack.clear();
+    }
+}
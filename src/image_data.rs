@@ -1,11 +1,17 @@
+use crate::config::ImageFingerprint;
 use eframe::epaint::TextureHandle;
+use image::DynamicImage;
 use std::ops::Neg;
+use std::sync::Arc;
 
 pub struct ImageData {
     pub texture: TextureHandle,
     pub width: usize,
     pub height: usize,
     pub bounds: [f64; 4],
+    /// Original samples if the image is deeper than 8 bits.
+    pub high_depth: Option<Arc<DynamicImage>>,
+    pub fingerprint: ImageFingerprint,
 }
 impl ImageData {
     pub fn get_rel_config_coords(&self, x1: f64, y1: f64, x2: f64, y2: f64) -> [f64; 4] {
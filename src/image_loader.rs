@@ -0,0 +1,283 @@
+use crate::app::RoIApp;
+use crate::config::ImageFingerprint;
+use crate::image_data::ImageData;
+use crate::orientation::{decode_oriented, transposes};
+use crate::window_level::{is_high_bit_depth, WindowLevel};
+use egui::{ColorImage, TextureOptions};
+use image::{DynamicImage, ImageReader};
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+
+/// Memory budget for decoded images kept around, in bytes.
+const IMAGE_CACHE_BYTES: usize = 512 * 1024 * 1024;
+
+type LoadResult = (PathBuf, Result<DecodedImage, String>);
+
+struct LoadRequest {
+    path: PathBuf,
+    /// Requested for display right now, so it goes before the prefetches.
+    urgent: bool,
+    window: WindowLevel,
+}
+
+#[derive(Clone)]
+pub struct DecodedImage {
+    pub display: ColorImage,
+    /// Original samples of 16-bit and float images, to redo the display with another window.
+    pub high_depth: Option<Arc<DynamicImage>>,
+    /// Window the display was made with.
+    pub window: WindowLevel,
+    /// Hashed along with decoding, so saving a config doesn't read the image on the UI thread.
+    pub fingerprint: ImageFingerprint,
+}
+impl DecodedImage {
+    fn new(img: DynamicImage, window: WindowLevel, fingerprint: ImageFingerprint) -> Self {
+        let display = window.to_color_image(&img);
+        let high_depth = is_high_bit_depth(&img).then(|| Arc::new(img));
+        Self {
+            display,
+            high_depth,
+            window,
+            fingerprint,
+        }
+    }
+    /// Redoes the display of a high bit depth image if the window changed.
+    pub fn apply_window(&mut self, window: WindowLevel) {
+        if let Some(img) = &self.high_depth {
+            if self.window != window {
+                self.display = window.to_color_image(img);
+                self.window = window;
+            }
+        }
+    }
+    fn size_in_bytes(&self) -> usize {
+        let display = self.display.pixels.len() * size_of::<egui::Color32>();
+        let original = self
+            .high_depth
+            .as_ref()
+            .map_or(0, |img| img.as_bytes().len());
+        display + original
+    }
+}
+
+/// Decodes images on a worker thread and keeps the recently used ones.
+#[derive(Default)]
+pub struct ImageLoader {
+    worker: Option<(Sender<LoadRequest>, Receiver<LoadResult>)>,
+    /// Least recently used first.
+    cache: VecDeque<(PathBuf, DecodedImage)>,
+    in_flight: HashSet<PathBuf>,
+    /// Image waiting to be shown once decoded.
+    pub loading: Option<PathBuf>,
+}
+impl ImageLoader {
+    fn request(&mut self, ctx: &egui::Context, path: &Path, urgent: bool, window: WindowLevel) {
+        if !self.in_flight.insert(path.to_path_buf()) {
+            // already queued, only bump it if needed
+            if !urgent {
+                return;
+            }
+        }
+        let (sender, _) = self.worker.get_or_insert_with(|| {
+            let (request_sender, request_receiver) = channel();
+            let (result_sender, result_receiver) = channel();
+            let ctx = ctx.clone();
+            thread::spawn(move || decode_worker(request_receiver, result_sender, ctx));
+            (request_sender, result_receiver)
+        });
+        let _ = sender.send(LoadRequest {
+            path: path.to_path_buf(),
+            urgent,
+            window,
+        });
+    }
+    /// Copies the image from the cache, marking it as the most recently used.
+    fn take_cached(&mut self, path: &Path) -> Option<DecodedImage> {
+        let idx = self.cache.iter().position(|(cached, _)| cached == path)?;
+        let entry = self.cache.remove(idx)?;
+        let img = entry.1.clone();
+        self.cache.push_back(entry);
+        Some(img)
+    }
+    fn insert(&mut self, path: PathBuf, img: DecodedImage) {
+        self.cache.retain(|(cached, _)| cached != &path);
+        self.cache.push_back((path, img));
+        let mut total: usize = self.cache.iter().map(|(_, img)| img.size_in_bytes()).sum();
+        // the newest image stays even if it alone is over budget
+        while total > IMAGE_CACHE_BYTES && self.cache.len() > 1 {
+            if let Some((_, evicted)) = self.cache.pop_front() {
+                total -= evicted.size_in_bytes();
+            }
+        }
+    }
+    fn is_cached_or_queued(&self, path: &Path) -> bool {
+        self.in_flight.contains(path) || self.cache.iter().any(|(cached, _)| cached == path)
+    }
+    pub fn is_loading(&self) -> bool {
+        self.loading.is_some()
+    }
+}
+
+/// Decodes requests one by one, urgent ones first.
+fn decode_worker(requests: Receiver<LoadRequest>, results: Sender<LoadResult>, ctx: egui::Context) {
+    let mut queue: VecDeque<LoadRequest> = VecDeque::new();
+    loop {
+        if queue.is_empty() {
+            match requests.recv() {
+                Ok(request) => enqueue(&mut queue, request),
+                Err(_) => return,
+            }
+        }
+        while let Ok(request) = requests.try_recv() {
+            enqueue(&mut queue, request);
+        }
+        let Some(request) = queue.pop_front() else {
+            continue;
+        };
+        let result = decode(&request.path, request.window).map_err(|err| err.to_string());
+        if results.send((request.path, result)).is_err() {
+            return;
+        }
+        ctx.request_repaint();
+    }
+}
+/// Reads the file once to both decode and hash it.
+fn decode(path: &Path, window: WindowLevel) -> image::ImageResult<DecodedImage> {
+    let bytes = fs::read(path)?;
+    let (img, orientation) = decode_oriented(ImageReader::new(Cursor::new(&bytes)))?;
+    let [width, height] = [img.width(), img.height()];
+    let stored_size = if transposes(orientation) {
+        [height, width]
+    } else {
+        [width, height]
+    };
+    let fingerprint = ImageFingerprint::new(&bytes, stored_size);
+    Ok(DecodedImage::new(img, window, fingerprint))
+}
+fn enqueue(queue: &mut VecDeque<LoadRequest>, request: LoadRequest) {
+    queue.retain(|queued| queued.path != request.path);
+    if request.urgent {
+        queue.push_front(request);
+    } else {
+        queue.push_back(request);
+    }
+}
+
+impl RoIApp {
+    /// Selects the image and shows it, decoding it in the background if it isn't cached.
+    /// Neighbours in the list are prefetched.
+    pub fn open_image(&mut self, ctx: &egui::Context, path: PathBuf) {
+        let window = self.image_settings.window;
+        if let Some(mut decoded) = self.image_loader.take_cached(&path) {
+            self.image_loader.loading = None;
+            decoded.apply_window(window);
+            self.show_image(ctx, decoded);
+        } else {
+            self.img_data = None;
+            self.image_loader.request(ctx, &path, true, window);
+            self.image_loader.loading = Some(path.to_path_buf());
+        }
+        self.prefetch_neighbours(ctx, &path);
+        self.selected_img = Some(path);
+        self.open_paired_config();
+    }
+    fn prefetch_neighbours(&mut self, ctx: &egui::Context, path: &Path) {
+        let Some(idx) = self.imgs_paths.iter().position(|p| p == path) else {
+            return;
+        };
+        let neighbours = [idx.checked_sub(1), Some(idx + 1)];
+        for neighbour in neighbours.into_iter().flatten() {
+            if let Some(neighbour) = self.imgs_paths.get(neighbour) {
+                if !self.image_loader.is_cached_or_queued(neighbour) {
+                    let window = self.image_settings.window;
+                    self.image_loader.request(ctx, neighbour, false, window);
+                }
+            }
+        }
+    }
+    fn show_image(&mut self, ctx: &egui::Context, decoded: DecodedImage) {
+        let [width, height] = decoded.display.size;
+        let options = TextureOptions::NEAREST;
+        if let Some(img_data) = &mut self.img_data {
+            img_data.texture.set(decoded.display, options);
+            img_data.width = width;
+            img_data.height = height;
+            img_data.high_depth = decoded.high_depth;
+            img_data.fingerprint = decoded.fingerprint;
+        } else {
+            self.img_data = Some(ImageData {
+                texture: ctx.load_texture("current_texture", decoded.display, options),
+                width,
+                height,
+                bounds: [0.0, 0.0, width as f64, height as f64],
+                high_depth: decoded.high_depth,
+                fingerprint: decoded.fingerprint,
+            });
+        };
+    }
+    /// Redoes the texture of the shown image after the window/level changed.
+    pub fn refresh_display(&mut self, ctx: &egui::Context) {
+        let Some(img_data) = &mut self.img_data else {
+            return;
+        };
+        if let Some(img) = &img_data.high_depth {
+            let display = self.image_settings.window.to_color_image(img);
+            img_data.texture.set(display, TextureOptions::NEAREST);
+            ctx.request_repaint();
+        }
+    }
+    /// Moves decoded images into the cache and shows the selected one when it's ready.
+    pub fn process_image_loads(&mut self, ctx: &egui::Context) {
+        let Some((_, receiver)) = &self.image_loader.worker else {
+            return;
+        };
+        let results: Vec<LoadResult> = receiver.try_iter().collect();
+        for (path, result) in results {
+            self.image_loader.in_flight.remove(&path);
+            match result {
+                Ok(mut decoded) => {
+                    if self.image_loader.loading.as_ref() == Some(&path) {
+                        self.image_loader.loading = None;
+                        decoded.apply_window(self.image_settings.window);
+                        self.show_image(ctx, decoded.clone());
+                    }
+                    self.image_loader.insert(path, decoded);
+                }
+                Err(err) => {
+                    if self.image_loader.loading.as_ref() == Some(&path) {
+                        self.image_loader.loading = None;
+                    }
+                    self.notifications
+                        .error(format!("Failed loading {}: {}", path.display(), err));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{test_dir, write_png};
+
+    #[test]
+    fn decoding_fingerprints_the_stored_image() {
+        let dir = test_dir("decode_fingerprint");
+        let path = dir.join("img.png");
+        write_png(&path, [4, 2], 6);
+        let decoded = decode(&path, WindowLevel::default()).unwrap();
+        assert_eq!(decoded.display.size, [2, 4]);
+        let read = ImageFingerprint::read(&path).unwrap();
+        assert_eq!(decoded.fingerprint.hash, read.hash);
+        assert_eq!(
+            [decoded.fingerprint.width, decoded.fingerprint.height],
+            [4, 2]
+        );
+        assert_eq!([read.width, read.height], [4, 2]);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::app::RoIApp;
+use crate::orientation::RoiFrame;
+use crate::window_level::WindowLevel;
+use egui::{ComboBox, DragValue, Window};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ImageSettings {
+    #[serde(skip)]
+    pub open: bool,
+    pub window: WindowLevel,
+    /// Frame of the rois in config files, the editor always works in the oriented one.
+    pub roi_frame: RoiFrame,
+    /// Save json configs as a bare roi array instead of the versioned form.
+    pub legacy_configs: bool,
+}
+
+impl RoIApp {
+    pub fn render_image_settings_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.image_settings.open;
+        let mut changed = false;
+        Window::new("Image settings")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let settings = &mut self.image_settings;
+                ComboBox::from_label("roi coords in files")
+                    .selected_text(settings.roi_frame.label())
+                    .show_ui(ui, |ui| {
+                        for frame in RoiFrame::ALL {
+                            ui.selectable_value(&mut settings.roi_frame, frame, frame.label());
+                        }
+                    })
+                    .response
+                    .on_hover_text(
+                        "Whether config coords follow the EXIF orientation or the stored pixels",
+                    );
+                ui.checkbox(
+                    &mut settings.legacy_configs,
+                    "save configs in the legacy format",
+                )
+                .on_hover_text("A bare roi array without the image, size and timestamps");
+                ui.separator();
+
+                let window = &mut settings.window;
+                ui.label("Window/level, applies to 16-bit and float images:");
+                changed |= ui
+                    .checkbox(&mut window.auto, "auto")
+                    .on_hover_text("Stretch the histogram, clipping the extreme 0.5% on each end")
+                    .changed();
+                ui.add_enabled_ui(!window.auto, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("low:");
+                        changed |= ui
+                            .add(
+                                DragValue::new(&mut window.low)
+                                    .speed(0.001)
+                                    .range(0.0..=1.0),
+                            )
+                            .changed();
+                        ui.label("high:");
+                        changed |= ui
+                            .add(
+                                DragValue::new(&mut window.high)
+                                    .speed(0.001)
+                                    .range(0.0..=1.0),
+                            )
+                            .changed();
+                    })
+                    .response
+                    .on_hover_text("Fractions of the full sample range, e.g. 1.0 = 65535");
+                });
+            });
+        self.image_settings.open = open;
+
+        if changed {
+            self.refresh_display(ctx);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::app::RoIApp;
+use crate::yolo::{read_classes, write_classes, CLASSES_FILE_NAME};
+use egui::ecolor::Hsva;
+use egui::{Color32, ComboBox, RichText, ScrollArea, Ui, Window};
+use std::path::{Path, PathBuf};
+
+/// Color of rois whose name is not a label.
+pub const UNKNOWN_LABEL_COLOR: Color32 = Color32::WHITE;
+
+/// Distinct color of the label at `idx`, hues are spread with the golden ratio.
+pub fn label_color(idx: usize) -> Color32 {
+    let hue = (idx as f32 * 0.618_034).fract();
+    Hsva::new(hue, 0.85, 1.0, 1.0).into()
+}
+
+pub fn roi_color(labels: &[String], name: &str) -> Color32 {
+    match labels.iter().position(|label| label == name) {
+        Some(idx) => label_color(idx),
+        None => UNKNOWN_LABEL_COLOR,
+    }
+}
+/// Whether the name is missing from a non-empty label set.
+pub fn is_unknown_label(labels: &[String], name: &str) -> bool {
+    !labels.is_empty() && !labels.iter().any(|label| label == name)
+}
+/// Picker of a label for `name`, listing the labels that contain the typed text first.
+/// Returns the picked label.
+pub fn label_picker(ui: &mut Ui, labels: &[String], id: usize, name: &str) -> Option<String> {
+    if labels.is_empty() {
+        return None;
+    }
+    let typed = name.to_lowercase();
+    let mut listed: Vec<(usize, &String)> = labels.iter().enumerate().collect();
+    listed.sort_by_key(|(_, label)| !label.to_lowercase().contains(&typed));
+    let mut picked = None;
+    ComboBox::from_id_salt(("label_picker", id))
+        .selected_text("")
+        .width(0.0)
+        .show_ui(ui, |ui| {
+            for (idx, label) in listed {
+                let text = RichText::new(label).color(label_color(idx));
+                if ui.selectable_label(label == name, text).clicked() {
+                    picked = Some(label.to_string());
+                }
+            }
+        });
+    picked
+}
+
+#[derive(Default)]
+pub struct LabelsWindow {
+    pub open: bool,
+    new_label: String,
+    /// Labels file, one name per line like YOLO `classes.txt`.
+    file_path: String,
+}
+
+impl RoIApp {
+    /// Replaces the labels with those of a file with one name per line.
+    pub fn load_labels(&mut self, path: &Path) {
+        match read_classes(path) {
+            Ok(labels) => {
+                self.notifications.info(format!(
+                    "Loaded {} labels from {}",
+                    labels.len(),
+                    path.display()
+                ));
+                self.labels = labels;
+            }
+            Err(err) => self.notifications.error(format!("{:#}", err)),
+        }
+    }
+    pub fn render_labels_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.labels_window.open;
+        let mut to_remove: Option<usize> = None;
+        let mut add_clicked = false;
+        let mut add_config_names = false;
+        let mut load_clicked = false;
+        let mut save_clicked = false;
+        Window::new("Labels")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                if self.project_path.is_some() {
+                    ui.label("saved with the project");
+                }
+                ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for (idx, label) in self.labels.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui.small_button("x").on_hover_text("Remove").clicked() {
+                                to_remove = Some(idx);
+                            }
+                            ui.label(RichText::new(label).color(label_color(idx)));
+                        });
+                    }
+                });
+                let window = &mut self.labels_window;
+                ui.horizontal(|ui| {
+                    let resp = ui.text_edit_singleline(&mut window.new_label);
+                    let entered =
+                        resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    add_clicked = ui.button("Add").clicked() || entered;
+                });
+                add_config_names = ui.button("Add labels of the current config").clicked();
+                ui.separator();
+                if window.file_path.is_empty() {
+                    if let Some(dir) = self.imgs_paths.first().and_then(|p| p.parent()) {
+                        window.file_path =
+                            dir.join(CLASSES_FILE_NAME).to_string_lossy().to_string();
+                    }
+                }
+                ui.horizontal(|ui| {
+                    ui.label("file:");
+                    ui.text_edit_singleline(&mut window.file_path);
+                });
+                ui.horizontal(|ui| {
+                    load_clicked = ui.button("Load").clicked();
+                    save_clicked = ui.button("Save").clicked();
+                });
+            });
+        self.labels_window.open = open;
+
+        if let Some(idx) = to_remove {
+            self.labels.remove(idx);
+        }
+        if add_clicked {
+            let label = self.labels_window.new_label.trim().to_string();
+            if !label.is_empty() && !self.labels.contains(&label) {
+                self.labels.push(label);
+            }
+            self.labels_window.new_label.clear();
+        }
+        if add_config_names {
+            for roi in &self.config_data.config {
+                if !self.labels.iter().any(|label| label == roi.class()) {
+                    self.labels.push(roi.class().to_string());
+                }
+            }
+        }
+        let file_path = PathBuf::from(&self.labels_window.file_path);
+        if load_clicked {
+            self.load_labels(&file_path);
+        }
+        if save_clicked {
+            match write_classes(&file_path, &self.labels) {
+                Ok(()) => self
+                    .notifications
+                    .info(format!("Saved {}", file_path.display())),
+                Err(err) => self.notifications.error(format!("{:#}", err)),
+            }
+        }
+    }
+}
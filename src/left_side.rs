@@ -1,9 +1,9 @@
 use crate::app::RoIApp;
+use crate::bindings::MouseAction;
 use crate::config_data::EditCoord;
-use crate::image_data::ImageData;
 use egui::scroll_area::ScrollBarVisibility;
-use egui::{ColorImage, ScrollArea, TextWrapMode, TextureFilter, TextureOptions};
-use kornia::io::functional::read_image_any;
+use egui::{ScrollArea, TextWrapMode};
+use std::path::PathBuf;
 
 impl RoIApp {
     pub fn render_left_side_panel(&mut self, ctx: &egui::Context) {
@@ -16,7 +16,9 @@ impl RoIApp {
                     .id_salt("imgs_scroll_area")
                     .scroll_bar_visibility(ScrollBarVisibility::AlwaysHidden)
                     .show(ui, |ui| {
+                        let delete = self.bindings.mouse(MouseAction::Delete);
                         let mut to_del: Option<usize> = None;
+                        let mut to_open: Option<PathBuf> = None;
                         for (idx, img_path) in self.imgs_paths.iter_mut().enumerate() {
                             let name = img_path.file_name().map(|name| name.to_string_lossy());
                             if let Some(name) = name {
@@ -27,51 +29,22 @@ impl RoIApp {
                                         name,
                                     )
                                     .on_hover_text(img_path.to_string_lossy());
-                                if resp.middle_clicked() {
+                                if delete.clicked(&resp) {
                                     to_del = Some(idx);
-                                };
-                                if resp.clicked() {
-                                    if let Ok(img) = read_image_any(img_path) {
-                                        let color_img = ColorImage::from_rgb(
-                                            [img.width(), img.height()],
-                                            img.as_slice(),
-                                        );
-
-                                        let options = TextureOptions {
-                                            magnification: TextureFilter::Nearest,
-                                            minification: TextureFilter::Nearest,
-                                            ..Default::default()
-                                        };
-                                        if let Some(img_data) = &mut self.img_data {
-                                            img_data.texture.set(color_img, options);
-                                            img_data.width = img.width();
-                                            img_data.height = img.height();
-                                        } else {
-                                            self.img_data = Some(ImageData {
-                                                texture: ctx.load_texture(
-                                                    "current_texture",
-                                                    color_img,
-                                                    options,
-                                                ),
-                                                width: img.width(),
-                                                height: img.height(),
-                                                bounds: [
-                                                    0.0,
-                                                    0.0,
-                                                    img.width() as f64,
-                                                    img.height() as f64,
-                                                ],
-                                            });
-                                        };
-                                    }
+                                } else if resp.clicked() {
+                                    to_open = Some(img_path.to_path_buf());
                                 };
                             };
                         }
+                        if let Some(path) = to_open {
+                            self.open_image(ctx, path);
+                        }
                         if let Some(idx) = to_del {
                             let removed = self.imgs_paths.remove(idx);
                             if Some(removed) == self.selected_img {
                                 self.selected_img = None;
                                 self.img_data = None;
+                                self.image_loader.loading = None;
                                 self.config_data.edit_idx = None;
                                 self.config_data.edit_coord = EditCoord::None;
                             };
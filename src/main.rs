@@ -1,27 +1,83 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
 mod app;
+mod bindings;
 mod central_panel;
+mod cli;
+mod coco;
 mod config;
 mod config_data;
+mod crop;
+mod dataset;
+mod dir_scan;
+mod dota;
 mod drop_files;
+mod export;
+mod history;
 mod image_data;
+mod image_loader;
+mod image_settings;
+mod labels;
 mod left_side;
+mod notifications;
+mod orientation;
+mod pairing;
+mod project;
 mod right_side;
+mod roi_inspector;
+mod session;
+mod shortcuts;
 mod top_side;
+mod unsaved_changes;
+mod voc;
+mod window_level;
+mod yolo;
 
 use crate::app::RoIApp;
+use crate::cli::Cli;
+use clap::Parser;
 use eframe::egui;
 
+/// Release builds on Windows start without a console, so the command line output
+/// goes to the console of the shell the app was started from.
+#[cfg(all(windows, not(debug_assertions)))]
+fn attach_parent_console() {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    // fails when started from the explorer, there's nothing to print to then
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
 fn main() -> eframe::Result {
+    #[cfg(all(windows, not(debug_assertions)))]
+    if std::env::args_os().len() > 1 {
+        attach_parent_console();
+    }
     env_logger::init();
+    let cli = Cli::parse();
+    if let Some(command) = cli.command {
+        if let Err(err) = command.run() {
+            eprintln!("Error: {:#}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1600.0, 900.0])
             .with_drag_and_drop(true),
         ..Default::default()
     };
-    eframe::run_native("RoI", options, Box::new(|cc| Ok(Box::new(RoIApp::new(cc)))))
+    eframe::run_native(
+        "RoI",
+        options,
+        Box::new(|cc| Ok(Box::new(RoIApp::new(cc, cli.open)))),
+    )
 }
 
 impl eframe::App for RoIApp {
@@ -30,6 +86,58 @@ impl eframe::App for RoIApp {
         self.render_right_side_panel(ctx);
         self.render_top_side_panel(ctx);
         self.render_center_panel(ctx);
+        self.render_export_window(ctx);
+        self.render_crop_window(ctx);
+        self.render_project_window(ctx);
+        self.render_labels_window(ctx);
+        self.render_log_window(ctx);
+        self.render_scan_settings_window(ctx);
+        self.render_image_settings_window(ctx);
+        self.render_help_window(ctx);
+        self.render_bindings_window(ctx);
+        self.process_image_loads(ctx);
         self.process_dropped_files(ctx);
+        self.process_dir_scans(ctx);
+        self.process_shortcuts(ctx);
+        self.process_close_request(ctx);
+        self.render_unsaved_changes_dialog(ctx);
+        self.render_toasts(ctx);
+    }
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.save_state(storage);
+    }
+}
+
+/// Fixtures shared by the tests of several modules.
+#[cfg(test)]
+mod test_util {
+    use image::codecs::png::PngEncoder;
+    use image::{ExtendedColorType, ImageEncoder};
+    use std::fs::{self, File};
+    use std::path::{Path, PathBuf};
+
+    /// Fresh directory for the files of one test.
+    pub fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("roi_test_{}_{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+    /// Black png of the stored `[width, height]` with an EXIF orientation tag, 1 to 8.
+    pub fn write_png(path: &Path, [width, height]: [u32; 2], exif_orientation: u16) {
+        // little endian TIFF header and a single IFD entry, orientation is a SHORT
+        let exif = [
+            b"II*\0\x08\0\0\0".as_slice(),
+            &[1, 0, 0x12, 0x01, 3, 0, 1, 0, 0, 0],
+            &exif_orientation.to_le_bytes(),
+            &[0; 6],
+        ]
+        .concat();
+        let mut encoder = PngEncoder::new(File::create(path).unwrap());
+        encoder.set_exif_metadata(exif).unwrap();
+        let pixels = vec![0; (width * height * 3) as usize];
+        encoder
+            .write_image(&pixels, width, height, ExtendedColorType::Rgb8)
+            .unwrap();
     }
 }
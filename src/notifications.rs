@@ -0,0 +1,159 @@
+use crate::app::RoIApp;
+use egui::{Align2, Color32, Frame, Id, RichText, ScrollArea, Window};
+use std::time::{Duration, Instant};
+
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+const ERROR_TOAST_DURATION: Duration = Duration::from_secs(8);
+const MAX_TOASTS: usize = 5;
+const MAX_LOG_ENTRIES: usize = 1000;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+impl Level {
+    fn color(&self, ui: &egui::Ui) -> Color32 {
+        match self {
+            Self::Info => ui.visuals().text_color(),
+            Self::Warning => ui.visuals().warn_fg_color,
+            Self::Error => ui.visuals().error_fg_color,
+        }
+    }
+    fn toast_duration(&self) -> Duration {
+        match self {
+            Self::Error => ERROR_TOAST_DURATION,
+            _ => TOAST_DURATION,
+        }
+    }
+}
+
+struct LogEntry {
+    level: Level,
+    message: String,
+    time: Instant,
+    /// Still shown as a toast.
+    toast: bool,
+}
+
+/// Messages for the user, shown as toasts for a few seconds and kept in the log window.
+pub struct Notifications {
+    pub log_open: bool,
+    entries: Vec<LogEntry>,
+    start: Instant,
+}
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            log_open: false,
+            entries: Vec::new(),
+            start: Instant::now(),
+        }
+    }
+}
+impl Notifications {
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(Level::Info, message.into());
+    }
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.push(Level::Warning, message.into());
+    }
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(Level::Error, message.into());
+    }
+    fn push(&mut self, level: Level, message: String) {
+        if self.entries.len() >= MAX_LOG_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.entries.push(LogEntry {
+            level,
+            message,
+            time: Instant::now(),
+            toast: true,
+        });
+    }
+    pub fn n_errors(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.level == Level::Error)
+            .count()
+    }
+}
+
+impl RoIApp {
+    pub fn render_toasts(&mut self, ctx: &egui::Context) {
+        let now = Instant::now();
+        let notifications = &mut self.notifications;
+        for entry in notifications.entries.iter_mut().filter(|entry| entry.toast) {
+            if now.duration_since(entry.time) >= entry.level.toast_duration() {
+                entry.toast = false;
+            }
+        }
+        let toasts: Vec<usize> = (0..notifications.entries.len())
+            .filter(|idx| notifications.entries[*idx].toast)
+            .collect();
+        if toasts.is_empty() {
+            return;
+        }
+        let shown = &toasts[toasts.len().saturating_sub(MAX_TOASTS)..];
+        let mut open_log = false;
+        egui::Area::new(Id::new("toasts"))
+            .anchor(Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                for &idx in shown {
+                    let entry = &mut notifications.entries[idx];
+                    let resp = Frame::popup(ui.style())
+                        .show(ui, |ui| {
+                            ui.set_max_width(400.0);
+                            ui.label(RichText::new(&entry.message).color(entry.level.color(ui)));
+                        })
+                        .response
+                        .interact(egui::Sense::click())
+                        .on_hover_text("Click to dismiss, the log keeps every message");
+                    if resp.clicked() {
+                        entry.toast = false;
+                        open_log |= entry.level != Level::Info;
+                    }
+                }
+            });
+        if open_log {
+            notifications.log_open = true;
+        }
+        ctx.request_repaint_after(Duration::from_millis(250));
+    }
+    pub fn render_log_window(&mut self, ctx: &egui::Context) {
+        let notifications = &mut self.notifications;
+        let mut open = notifications.log_open;
+        let mut clear_clicked = false;
+        Window::new("Log")
+            .open(&mut open)
+            .default_size([600.0, 300.0])
+            .collapsible(false)
+            .show(ctx, |ui| {
+                clear_clicked = ui.button("Clear").clicked();
+                ui.separator();
+                ScrollArea::vertical()
+                    .auto_shrink(false)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for entry in &notifications.entries {
+                            let secs = entry.time.duration_since(notifications.start).as_secs();
+                            let text = format!(
+                                "[{:02}:{:02}:{:02}] {}",
+                                secs / 3600,
+                                secs / 60 % 60,
+                                secs % 60,
+                                entry.message
+                            );
+                            ui.label(RichText::new(text).color(entry.level.color(ui)));
+                        }
+                    });
+            });
+        notifications.log_open = open;
+        if clear_clicked {
+            notifications.entries.clear();
+        }
+    }
+}
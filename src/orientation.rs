@@ -0,0 +1,241 @@
+use crate::config::JsonConfig;
+use anyhow::Context;
+use clap::ValueEnum;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Seek};
+use std::path::Path;
+
+/// Frame the roi coords in config files refer to.
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Copy, ValueEnum)]
+pub enum RoiFrame {
+    /// The image as displayed, with its EXIF orientation applied.
+    #[default]
+    Oriented,
+    /// The pixels as stored in the file, ignoring EXIF orientation.
+    Raw,
+}
+impl RoiFrame {
+    pub const ALL: [Self; 2] = [Self::Oriented, Self::Raw];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Oriented => "oriented",
+            Self::Raw => "raw sensor",
+        }
+    }
+}
+
+/// EXIF orientation of the image, read without decoding the pixels.
+pub fn read_orientation(path: &Path) -> Orientation {
+    ImageReader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .ok()
+        .and_then(|reader| reader.into_decoder().ok())
+        .and_then(|mut decoder| decoder.orientation().ok())
+        .unwrap_or(Orientation::NoTransforms)
+}
+
+/// Whether the orientation swaps width and height.
+pub fn transposes(orientation: Orientation) -> bool {
+    matches!(
+        orientation,
+        Orientation::Rotate90
+            | Orientation::Rotate270
+            | Orientation::Rotate90FlipH
+            | Orientation::Rotate270FlipH
+    )
+}
+/// Pixel `[width, height]` of the image in the frame roi coords refer to,
+/// read without decoding the pixels.
+pub fn image_size(path: &Path, frame: RoiFrame) -> anyhow::Result<[u32; 2]> {
+    let (width, height) = image::image_dimensions(path)
+        .with_context(|| format!("reading size of {}", path.display()))?;
+    if frame == RoiFrame::Oriented && transposes(read_orientation(path)) {
+        Ok([height, width])
+    } else {
+        Ok([width, height])
+    }
+}
+
+/// Decodes the image and turns it the way its EXIF orientation says.
+/// Also returns the applied orientation.
+pub fn open_oriented(path: &Path) -> image::ImageResult<(DynamicImage, Orientation)> {
+    decode_oriented(ImageReader::open(path)?)
+}
+/// Like [`open_oriented`], for images already read into memory.
+pub fn decode_oriented(
+    reader: ImageReader<impl BufRead + Seek>,
+) -> image::ImageResult<(DynamicImage, Orientation)> {
+    let mut decoder = reader.with_guessed_format()?.into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut img = DynamicImage::from_decoder(decoder)?;
+    img.apply_orientation(orientation);
+    Ok((img, orientation))
+}
+
+/// Where a relative `[x, y]` point of the raw image ends up in the oriented one.
+fn orient_point(orientation: Orientation, [x, y]: [f64; 2]) -> [f64; 2] {
+    match orientation {
+        Orientation::NoTransforms => [x, y],
+        Orientation::Rotate90 => [1.0 - y, x],
+        Orientation::Rotate180 => [1.0 - x, 1.0 - y],
+        Orientation::Rotate270 => [y, 1.0 - x],
+        Orientation::FlipHorizontal => [1.0 - x, y],
+        Orientation::FlipVertical => [x, 1.0 - y],
+        Orientation::Rotate90FlipH => [y, x],
+        Orientation::Rotate270FlipH => [1.0 - y, 1.0 - x],
+    }
+}
+fn inverse(orientation: Orientation) -> Orientation {
+    match orientation {
+        Orientation::Rotate90 => Orientation::Rotate270,
+        Orientation::Rotate270 => Orientation::Rotate90,
+        // the rest undo themselves
+        other => other,
+    }
+}
+fn transform_config(config: &[JsonConfig], orientation: Orientation) -> Vec<JsonConfig> {
+    let transposed = transposes(orientation);
+    // a mirrored roi turns the other way
+    let mirrored = matches!(
+        orientation,
+        Orientation::FlipHorizontal
+            | Orientation::FlipVertical
+            | Orientation::Rotate90FlipH
+            | Orientation::Rotate270FlipH
+    );
+    config
+        .iter()
+        .map(|roi| {
+            let [cx, cy] = orient_point(orientation, roi.get_center());
+            let [mut half_w, mut half_h] = [(roi.x2 - roi.x1) / 2.0, (roi.y2 - roi.y1) / 2.0];
+            if transposed {
+                std::mem::swap(&mut half_w, &mut half_h);
+            }
+            JsonConfig {
+                x1: cx - half_w,
+                y1: cy - half_h,
+                x2: cx + half_w,
+                y2: cy + half_h,
+                angle: if mirrored { -roi.angle } else { roi.angle },
+                ..roi.clone()
+            }
+        })
+        .collect()
+}
+/// Converts rois of the raw image to the oriented one.
+pub fn orient_config(config: &[JsonConfig], orientation: Orientation) -> Vec<JsonConfig> {
+    transform_config(config, orientation)
+}
+/// Converts rois of the oriented image back to the raw one.
+pub fn unorient_config(config: &[JsonConfig], orientation: Orientation) -> Vec<JsonConfig> {
+    transform_config(config, inverse(orientation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{test_dir, write_png};
+
+    #[test]
+    fn image_size_swaps_for_rotated_exif() {
+        let dir = test_dir("image_size");
+        let path = dir.join("img.png");
+        write_png(&path, [4, 2], 6);
+        assert_eq!(read_orientation(&path), Orientation::Rotate90);
+        assert_eq!(image_size(&path, RoiFrame::Oriented).unwrap(), [2, 4]);
+        assert_eq!(image_size(&path, RoiFrame::Raw).unwrap(), [4, 2]);
+
+        write_png(&path, [4, 2], 3);
+        assert_eq!(image_size(&path, RoiFrame::Oriented).unwrap(), [4, 2]);
+    }
+
+    const ALL_ORIENTATIONS: [Orientation; 8] = [
+        Orientation::NoTransforms,
+        Orientation::Rotate90,
+        Orientation::Rotate180,
+        Orientation::Rotate270,
+        Orientation::FlipHorizontal,
+        Orientation::FlipVertical,
+        Orientation::Rotate90FlipH,
+        Orientation::Rotate270FlipH,
+    ];
+
+    fn assert_same_roi(a: &JsonConfig, b: &JsonConfig) {
+        for (a, b) in [
+            (a.x1, b.x1),
+            (a.y1, b.y1),
+            (a.x2, b.x2),
+            (a.y2, b.y2),
+            (a.angle, b.angle),
+        ] {
+            assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+        }
+    }
+
+    #[test]
+    fn unorient_undoes_orient_for_every_orientation() {
+        let roi = JsonConfig {
+            x1: 0.1,
+            y1: 0.2,
+            x2: 0.4,
+            y2: 0.7,
+            angle: 30.0,
+            name: String::from("cat"),
+            ..Default::default()
+        };
+        for orientation in ALL_ORIENTATIONS {
+            let oriented = orient_config(std::slice::from_ref(&roi), orientation);
+            let raw = unorient_config(&oriented, orientation);
+            assert_same_roi(&raw[0], &roi);
+            assert_eq!(raw[0].name, "cat");
+        }
+    }
+
+    #[test]
+    fn rotate90_turns_the_box_clockwise() {
+        let roi = JsonConfig {
+            x1: 0.1,
+            y1: 0.2,
+            x2: 0.4,
+            y2: 0.7,
+            angle: 30.0,
+            ..Default::default()
+        };
+        let expected = JsonConfig {
+            x1: 0.3,
+            y1: 0.1,
+            x2: 0.8,
+            y2: 0.4,
+            angle: 30.0,
+            ..Default::default()
+        };
+        assert_same_roi(
+            &orient_config(std::slice::from_ref(&roi), Orientation::Rotate90)[0],
+            &expected,
+        );
+
+        let mirrored = orient_config(&[roi], Orientation::FlipHorizontal);
+        let expected = JsonConfig {
+            x1: 0.6,
+            y1: 0.2,
+            x2: 0.9,
+            y2: 0.7,
+            angle: -30.0,
+            ..Default::default()
+        };
+        assert_same_roi(&mirrored[0], &expected);
+    }
+
+    #[test]
+    fn open_oriented_applies_the_exif_orientation() {
+        let dir = test_dir("open_oriented");
+        let path = dir.join("img.png");
+        write_png(&path, [4, 2], 8);
+        let (img, orientation) = open_oriented(&path).unwrap();
+        assert_eq!(orientation, Orientation::Rotate270);
+        assert_eq!([img.width(), img.height()], [2, 4]);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::app::RoIApp;
+use crate::config::{is_config_of_image, recorded_image};
+use crate::dataset::IMAGE_EXTENSIONS;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Sidecar file mapping config file names in its directory to image paths relative to it,
+/// e.g. `{"cats.json": "../images/cat_001.png"}`.
+pub const PAIRS_FILE_NAME: &str = "roi_pairs.json";
+
+/// `roi_pairs.json` sidecars, each read once per directory.
+#[derive(Default)]
+pub struct Sidecars {
+    /// Pairs of every directory looked at, empty for directories without a sidecar.
+    pairs: HashMap<PathBuf, HashMap<String, String>>,
+}
+impl Sidecars {
+    /// Image the config is assigned to in the `roi_pairs.json` next to it.
+    fn image_of(&mut self, config_path: &Path) -> Option<PathBuf> {
+        let dir = config_path.parent()?;
+        let pairs = self
+            .pairs
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| read_sidecar(dir));
+        let config_name = config_path.file_name()?.to_string_lossy();
+        pairs.get(config_name.as_ref()).map(|img| dir.join(img))
+    }
+    /// Image assigned to the config by a `roi_pairs.json` sidecar or recorded in the config itself.
+    pub fn assigned_image(&mut self, config_path: &Path) -> Option<PathBuf> {
+        self.image_of(config_path)
+            .or_else(|| recorded_image(config_path))
+    }
+    /// Canonical path of the assigned image, as [`ConfigImages`] compares them.
+    pub fn resolve(&mut self, config_path: &Path) -> Option<PathBuf> {
+        self.assigned_image(config_path).map(|img| canonical(&img))
+    }
+}
+fn read_sidecar(dir: &Path) -> HashMap<String, String> {
+    fs::read_to_string(dir.join(PAIRS_FILE_NAME))
+        .ok()
+        .and_then(|json_string| serde_json::from_str(&json_string).ok())
+        .unwrap_or_default()
+}
+
+/// YOLO layout: `<stem>.txt` next to the image, or in a `labels` dir beside the `images` dir.
+fn is_yolo_label_of_image(config_path: &Path, img_path: &Path) -> bool {
+    if config_path.extension().is_none_or(|ext| ext != "txt")
+        || config_path.file_stem() != img_path.file_stem()
+    {
+        return false;
+    }
+    let (Some(label_dir), Some(img_dir)) = (config_path.parent(), img_path.parent()) else {
+        return false;
+    };
+    label_dir == img_dir
+        || (label_dir.file_name().is_some_and(|name| name == "labels")
+            && label_dir.parent() == img_dir.parent())
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn is_named_after_image(config_path: &Path, img_path: &Path) -> bool {
+    is_config_of_image(config_path, img_path) || is_yolo_label_of_image(config_path, img_path)
+}
+
+/// Images assigned to configs, read once per config until it is written again.
+/// Configs found by a directory scan come with their image already resolved by the worker.
+#[derive(Default)]
+pub struct ConfigImages {
+    /// Canonical path of the assigned image, `None` for configs paired by their name.
+    assigned: HashMap<PathBuf, Option<PathBuf>>,
+    canonical_imgs: HashMap<PathBuf, PathBuf>,
+    sidecars: Sidecars,
+}
+impl ConfigImages {
+    /// Checks whether the config belongs to the image: by a `roi_pairs.json` sidecar or the image
+    /// recorded in the config if there is one, else by the `roi_<image stem>_(N).json` naming
+    /// or the YOLO labels layout.
+    pub fn belongs(&mut self, config_path: &Path, img_path: &Path) -> bool {
+        if !self.assigned.contains_key(config_path) {
+            let assigned = self.sidecars.resolve(config_path);
+            self.assigned.insert(config_path.to_path_buf(), assigned);
+        }
+        match &self.assigned[config_path] {
+            Some(assigned) => {
+                let img_path = self
+                    .canonical_imgs
+                    .entry(img_path.to_path_buf())
+                    .or_insert_with(|| canonical(img_path));
+                assigned == img_path
+            }
+            None => is_named_after_image(config_path, img_path),
+        }
+    }
+    /// Takes the image resolved elsewhere with [`Sidecars::resolve`].
+    pub fn insert(&mut self, config_path: PathBuf, assigned: Option<PathBuf>) {
+        self.assigned.insert(config_path, assigned);
+    }
+    /// Reads the image of the config again on the next check, after it was written.
+    pub fn forget(&mut self, config_path: &Path) {
+        self.assigned.remove(config_path);
+    }
+    pub fn clear(&mut self) {
+        self.assigned.clear();
+        self.canonical_imgs.clear();
+        self.sidecars = Sidecars::default();
+    }
+}
+
+/// Image of the config like [`ConfigImages::belongs`] pairs them,
+/// looking for `<image stem>.<ext>` next to a `roi_<image stem>_(N).json` config.
+pub fn find_image_of_config(config_path: &Path) -> Option<PathBuf> {
+    if let Some(img_path) = Sidecars::default().assigned_image(config_path) {
+        return Some(img_path);
+    }
+    let config_name = config_path.file_name()?.to_string_lossy();
+    let (img_stem, _) = config_name.strip_prefix("roi_")?.rsplit_once("_(")?;
+    IMAGE_EXTENSIONS
+        .iter()
+        .map(|ext| config_path.with_file_name(format!("{}.{}", img_stem, ext)))
+        .find(|img_path| img_path.is_file())
+}
+
+/// Configs listed for the selected image, cached until the image or the config list changes.
+#[derive(Default)]
+pub struct PairedConfigs {
+    image: Option<PathBuf>,
+    /// [`RoIApp::configs_generation`] the paths were collected at.
+    generation: u64,
+    paths: Vec<PathBuf>,
+}
+
+impl RoIApp {
+    /// Configs of the selected image, in list order.
+    pub fn paired_configs(&mut self) -> &[PathBuf] {
+        let Some(img_path) = &self.selected_img else {
+            return &[];
+        };
+        let cache = &mut self.paired_configs;
+        if cache.image.as_ref() != Some(img_path) || cache.generation != self.configs_generation {
+            let config_images = &mut self.config_images;
+            cache.paths = self
+                .configs_paths
+                .iter()
+                .filter(|config_path| config_images.belongs(config_path, img_path))
+                .cloned()
+                .collect();
+            cache.image = Some(img_path.to_path_buf());
+            cache.generation = self.configs_generation;
+        }
+        &self.paired_configs.paths
+    }
+    /// Configs shown in the right panel.
+    pub fn listed_configs(&mut self) -> &[PathBuf] {
+        if self.show_all_configs {
+            &self.configs_paths
+        } else {
+            self.paired_configs()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{write_config, JsonConfig};
+    use crate::test_util::{test_dir, write_png};
+
+    #[test]
+    fn configs_pair_by_sidecar_recorded_image_and_name() {
+        let dir = test_dir("pairing");
+        for name in ["a", "b"] {
+            write_png(&dir.join(format!("{}.png", name)), [4, 2], 1);
+        }
+        let (a, b) = (dir.join("a.png"), dir.join("b.png"));
+        fs::write(dir.join(PAIRS_FILE_NAME), r#"{"cats.json": "b.png"}"#).unwrap();
+        let roi = JsonConfig {
+            x2: 1.0,
+            y2: 1.0,
+            name: String::from("cat"),
+            ..Default::default()
+        };
+        for config_name in ["cats.json", "dogs.json", "roi_a_(1).json"] {
+            write_config(&dir.join(config_name), std::slice::from_ref(&roi), None).unwrap();
+        }
+        write_config(&dir.join("dogs.json"), &[roi], Some(&b)).unwrap();
+
+        let mut config_images = ConfigImages::default();
+        assert!(config_images.belongs(&dir.join("cats.json"), &b));
+        assert!(!config_images.belongs(&dir.join("cats.json"), &a));
+        assert!(config_images.belongs(&dir.join("dogs.json"), &b));
+        assert!(config_images.belongs(&dir.join("roi_a_(1).json"), &a));
+        assert!(!config_images.belongs(&dir.join("roi_a_(1).json"), &b));
+
+        // resolved by a scan worker
+        config_images.insert(dir.join("roi_a_(1).json"), Some(canonical(&b)));
+        assert!(config_images.belongs(&dir.join("roi_a_(1).json"), &b));
+        config_images.forget(&dir.join("roi_a_(1).json"));
+        assert!(config_images.belongs(&dir.join("roi_a_(1).json"), &a));
+    }
+}
@@ -0,0 +1,286 @@
+use crate::app::RoIApp;
+use crate::crop::CropOptions;
+use crate::export::ExportFormat;
+use crate::orientation::RoiFrame;
+use crate::unsaved_changes::PendingAction;
+use crate::window_level::WindowLevel;
+use anyhow::{bail, Context};
+use egui::Window;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+pub const PROJECT_EXTENSION: &str = "roiproj";
+const PROJECT_VERSION: u32 = 1;
+
+/// Dataset shared between annotators. Paths are relative to the project file
+/// and use `/` separators, so the project can be committed along with the data.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Project {
+    pub version: u32,
+    pub images: Vec<String>,
+    pub configs: Vec<String>,
+    /// Roi names used in the dataset.
+    pub labels: Vec<String>,
+    pub settings: ProjectSettings,
+}
+impl Default for Project {
+    fn default() -> Self {
+        Self {
+            version: PROJECT_VERSION,
+            images: Vec::new(),
+            configs: Vec::new(),
+            labels: Vec::new(),
+            settings: ProjectSettings::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ProjectSettings {
+    pub roi_frame: RoiFrame,
+    pub window: WindowLevel,
+    pub export_format: ExportFormat,
+    pub crop: CropOptions,
+}
+
+pub fn is_project_path(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == PROJECT_EXTENSION)
+}
+
+pub fn read_project(path: &Path) -> anyhow::Result<Project> {
+    let json_string =
+        fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let project: Project = serde_json::from_str(&json_string)
+        .with_context(|| format!("parsing {}", path.display()))?;
+    if project.version > PROJECT_VERSION {
+        bail!(
+            "{} has version {}, only up to {} is supported",
+            path.display(),
+            project.version,
+            PROJECT_VERSION
+        );
+    }
+    Ok(project)
+}
+
+pub fn write_project(path: &Path, project: &Project) -> anyhow::Result<()> {
+    let json_string = serde_json::to_string_pretty(project)?;
+    fs::write(path, json_string).with_context(|| format!("writing {}", path.display()))
+}
+
+impl Project {
+    /// Absolute paths of the images and configs, given the project file directory.
+    pub fn resolve(&self, dir: &Path) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let resolve = |paths: &[String]| -> Vec<PathBuf> {
+            paths
+                .iter()
+                .map(|path| normalize(&dir.join(path)))
+                .collect()
+        };
+        (resolve(&self.images), resolve(&self.configs))
+    }
+}
+
+/// Images and configs of the projects among `inputs` in place of the project files.
+pub fn expand_projects(inputs: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut expanded = Vec::with_capacity(inputs.len());
+    for path in inputs {
+        if !is_project_path(path) {
+            expanded.push(path.to_path_buf());
+            continue;
+        }
+        let dir = project_dir(path);
+        let (imgs_paths, configs_paths) = read_project(path)?.resolve(&dir);
+        expanded.extend(imgs_paths);
+        expanded.extend(configs_paths.into_iter().filter(|path| path.is_file()));
+    }
+    Ok(expanded)
+}
+
+fn project_dir(project_path: &Path) -> PathBuf {
+    let dir = project_path.parent().unwrap_or(Path::new(""));
+    std::path::absolute(dir).unwrap_or(dir.to_path_buf())
+}
+
+/// Removes `.` and `..` components without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// `path` relative to the `base` directory with `/` separators.
+/// Paths on another drive stay absolute.
+pub fn relative_path(path: &Path, base: &Path) -> String {
+    let path = normalize(path);
+    let base = normalize(base);
+    let mut path_components = path.components().peekable();
+    let mut base_components = base.components().peekable();
+    if path_components.peek() != base_components.peek() {
+        return path.to_string_lossy().replace('\\', "/");
+    }
+    while path_components.peek().is_some() && path_components.peek() == base_components.peek() {
+        path_components.next();
+        base_components.next();
+    }
+    let parts: Vec<String> = base_components
+        .map(|_| String::from(".."))
+        .chain(path_components.map(|c| c.as_os_str().to_string_lossy().to_string()))
+        .collect();
+    parts.join("/")
+}
+
+#[derive(Default)]
+pub struct ProjectWindow {
+    pub open: bool,
+    pub path: String,
+}
+
+impl RoIApp {
+    /// Replaces the listed files, labels and settings with those of the project.
+    /// Files missing on disk are skipped, except for configs that are yet to be saved.
+    pub fn open_project(&mut self, path: PathBuf) -> anyhow::Result<()> {
+        let project = read_project(&path)?;
+        let (imgs_paths, configs_paths) = project.resolve(&project_dir(&path));
+        let n_listed = imgs_paths.len();
+        self.imgs_paths = imgs_paths.into_iter().filter(|p| p.is_file()).collect();
+        self.configs_paths = configs_paths;
+        self.configs_generation += 1;
+        self.config_images.clear();
+        let n_missing = n_listed - self.imgs_paths.len();
+        if n_missing > 0 {
+            self.notifications.warn(format!(
+                "{} images of {} no longer exist",
+                n_missing,
+                path.display()
+            ));
+        }
+        self.labels = project.labels;
+
+        let settings = project.settings;
+        self.image_settings.roi_frame = settings.roi_frame;
+        self.image_settings.window = settings.window;
+        self.export_settings.format = settings.export_format;
+        self.crop_settings.options = settings.crop;
+
+        self.close_config();
+        self.selected_img = None;
+        self.img_data = None;
+        self.image_loader.loading = None;
+        self.project_path = Some(path);
+        Ok(())
+    }
+    /// Writes the listed files, labels and settings as a project.
+    pub fn save_project(&mut self, path: PathBuf) -> anyhow::Result<()> {
+        let dir = project_dir(&path);
+        let relative = |paths: &[PathBuf]| -> Vec<String> {
+            paths.iter().map(|p| relative_path(p, &dir)).collect()
+        };
+        let project = Project {
+            images: relative(&self.imgs_paths),
+            configs: relative(&self.configs_paths),
+            labels: self.labels.clone(),
+            settings: ProjectSettings {
+                roi_frame: self.image_settings.roi_frame,
+                window: self.image_settings.window,
+                export_format: self.export_settings.format,
+                crop: self.crop_settings.options.clone(),
+            },
+            ..Default::default()
+        };
+        write_project(&path, &project)?;
+        self.project_path = Some(path);
+        Ok(())
+    }
+    pub fn render_project_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.project_window.open;
+        let mut new_clicked = false;
+        let mut open_clicked = false;
+        let mut save_clicked = false;
+        Window::new("Project")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let window = &mut self.project_window;
+                match &self.project_path {
+                    Some(path) => ui.label(format!("current: {}", path.display())),
+                    None => ui.label("no project open"),
+                };
+                if window.path.is_empty() {
+                    let default_path = match &self.project_path {
+                        Some(path) => Some(path.to_path_buf()),
+                        None => self
+                            .imgs_paths
+                            .first()
+                            .and_then(|p| p.parent())
+                            .map(|dir| dir.join(format!("dataset.{}", PROJECT_EXTENSION))),
+                    };
+                    if let Some(default_path) = default_path {
+                        window.path = default_path.to_string_lossy().to_string();
+                    }
+                }
+                ui.horizontal(|ui| {
+                    ui.label("file:");
+                    ui.text_edit_singleline(&mut window.path);
+                });
+                ui.label(format!(
+                    "{} images, {} configs, {} labels",
+                    self.imgs_paths.len(),
+                    self.configs_paths.len(),
+                    self.labels.len()
+                ));
+                ui.horizontal(|ui| {
+                    new_clicked = ui
+                        .button("New")
+                        .on_hover_text("Create a project from the listed files")
+                        .clicked();
+                    open_clicked = ui.button("Open").clicked();
+                    save_clicked = self.project_path.is_some() && ui.button("Save").clicked();
+                });
+            });
+        self.project_window.open = open;
+
+        let path = std::path::absolute(&self.project_window.path)
+            .unwrap_or_else(|_| PathBuf::from(&self.project_window.path));
+        if new_clicked {
+            if path.exists() {
+                self.notifications
+                    .error(format!("{} already exists", path.display()));
+            } else {
+                match self.save_project(path.to_path_buf()) {
+                    Ok(()) => self
+                        .notifications
+                        .info(format!("Created {}", path.display())),
+                    Err(err) => self
+                        .notifications
+                        .error(format!("Creating project failed: {:#}", err)),
+                }
+            }
+        }
+        if open_clicked {
+            self.request_action(PendingAction::OpenProject(path));
+        }
+        if save_clicked {
+            if let Some(path) = self.project_path.clone() {
+                match self.save_project(path.to_path_buf()) {
+                    Ok(()) => self.notifications.info(format!("Saved {}", path.display())),
+                    Err(err) => self
+                        .notifications
+                        .error(format!("Saving project failed: {:#}", err)),
+                }
+            }
+        }
+    }
+}
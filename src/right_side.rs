@@ -1,31 +1,32 @@
 use crate::app::RoIApp;
-use crate::config::JsonConfig;
-use crate::config_data::EditCoord;
+use crate::bindings::{MouseAction, MouseBinding};
+use crate::config::{next_free_config_path, JsonConfig};
+use crate::labels::{is_unknown_label, roi_color};
+use crate::unsaved_changes::PendingAction;
 use egui::scroll_area::ScrollBarVisibility;
-use egui::{ScrollArea, TextWrapMode};
-use std::fs::read_to_string;
+use egui::{RichText, ScrollArea, TextWrapMode};
+use std::path::PathBuf;
 
 impl RoIApp {
     pub fn render_right_side_panel(&mut self, ctx: &egui::Context) {
+        let draw_hint = format!(
+            "{} drag on the image to draw new rois",
+            self.bindings.mouse(MouseAction::Draw).format(ctx)
+        );
         egui::SidePanel::right("configs_panel")
             .resizable(false)
             .show(ctx, |ui| {
                 ui.style_mut().wrap_mode = Some(TextWrapMode::Extend);
-                ui.heading("Configs:");
+                ui.horizontal(|ui| {
+                    ui.heading("Configs:");
+                    ui.checkbox(&mut self.show_all_configs, "all")
+                        .on_hover_text("List configs of every image, not only the selected one");
+                });
                 if let Some(img_path) = &self.selected_img {
                     if ui.button("create new").clicked() {
-                        if let Some(name) = img_path.file_stem() {
-                            let str_name = name.to_string_lossy();
-                            for i in 1usize..=100usize {
-                                let new_name = format!("roi_{}_({}).json", str_name, i);
-                                let new_path = img_path.with_file_name(new_name);
-                                if new_path.exists() {
-                                    continue;
-                                } else {
-                                    self.configs_paths.push(new_path);
-                                    break;
-                                };
-                            }
+                        if let Some(new_path) = next_free_config_path(img_path, &self.configs_paths)
+                        {
+                            self.list_config(new_path);
                         }
                     }
                 };
@@ -33,102 +34,147 @@ impl RoIApp {
                     .id_salt("configs_scroll_area")
                     .scroll_bar_visibility(ScrollBarVisibility::AlwaysHidden)
                     .show(ui, |ui| {
-                        let mut to_del: Option<usize> = None;
-                        for (idx, config_path) in self.configs_paths.iter_mut().enumerate() {
-                            let name = config_path.file_name().map(|name| name.to_string_lossy());
-                            if let Some(name) = name {
-                                let resp = ui
-                                    .selectable_value(
-                                        &mut self.selected_config,
-                                        Some(config_path.to_path_buf()),
-                                        name,
-                                    )
-                                    .on_hover_text(config_path.to_string_lossy());
-
-                                if resp.middle_clicked() {
-                                    to_del = Some(idx);
-                                };
-                                if resp.clicked() {
-                                    if !config_path.exists() {
-                                        self.config_data = Default::default();
-                                    } else if let Ok(json_string) = read_to_string(&config_path) {
-                                        if let Ok(config) =
-                                            serde_json::from_str::<Vec<JsonConfig>>(&json_string)
-                                        {
-                                            self.config_data.config = config;
-                                            self.config_data.edit_coord = EditCoord::None;
-                                        }
-                                    }
-                                };
-                                if let Some(selected_config) = &self.selected_config {
-                                    if selected_config == &config_path.to_path_buf() {
-                                        if let Some(img_data) = &self.img_data {
-                                            ui.horizontal_top(|ui| {
-                                                ui.label("+");
-                                                let button = ui.small_button("create new roi");
-                                                if button.clicked() {
-                                                    let [bx1, by1, bx2, by2] = img_data.bounds;
-                                                    let [x1, y1, x2, y2] = img_data
-                                                        .get_rel_config_coords(
-                                                            bx1 + 0.3 * (bx2 - bx1),
-                                                            by1 - 0.3 * (by1 - by2),
-                                                            bx2 - 0.3 * (bx2 - bx1),
-                                                            by2 + 0.3 * (by1 - by2),
-                                                        );
-                                                    let new_roi = JsonConfig {
-                                                        x1,
-                                                        y1,
-                                                        x2,
-                                                        y2,
-                                                        name: String::from("new_roi"),
-                                                    };
-                                                    self.config_data.config.push(new_roi);
-                                                }
-                                            });
-                                        };
-                                        ScrollArea::vertical()
-                                            .id_salt("editing_config_area")
-                                            .scroll_bar_visibility(
-                                                ScrollBarVisibility::AlwaysHidden,
-                                            )
-                                            .max_height(ui.available_height() * 0.9)
-                                            .show(ui, |ui| {
-                                                let mut to_del: Option<usize> = None;
-                                                for (idx, c) in
-                                                    self.config_data.config.iter_mut().enumerate()
-                                                {
-                                                    ui.horizontal_top(|ui| {
-                                                        ui.label(">");
-                                                        if Some(idx) == self.config_data.edit_idx {
-                                                            ui.text_edit_singleline(&mut c.name);
-                                                        } else {
-                                                            let button = ui.small_button(&c.name);
-                                                            if button.clicked() {
-                                                                self.config_data.edit_idx =
-                                                                    Some(idx);
-                                                            };
-                                                            if button.middle_clicked() {
-                                                                to_del = Some(idx);
-                                                            };
-                                                        };
-                                                    });
-                                                }
-                                                if let Some(del_idx) = to_del {
-                                                    self.config_data.safely_remove_roi(del_idx);
-                                                }
-                                            });
-                                    }
-                                }
-                            };
+                        let delete = self.bindings.mouse(MouseAction::Delete);
+                        let mut clicked = ConfigClicks::default();
+                        let selected = self.selected_config.clone();
+                        let dirty = self.config_data.is_dirty();
+                        // the open config is followed by its editor
+                        let listed = self.listed_configs();
+                        let editor_pos = listed
+                            .iter()
+                            .position(|path| Some(path) == selected.as_ref())
+                            .map(|idx| idx + 1);
+                        let n_before = editor_pos.unwrap_or(listed.len());
+                        let names = ConfigNames {
+                            selected: selected.as_ref(),
+                            dirty,
+                            delete,
+                        };
+                        names.render(ui, &listed[..n_before], &mut clicked);
+                        if editor_pos.is_some() {
+                            self.render_config_editor(ui, delete, &draw_hint);
+                            names.render(ui, &self.listed_configs()[n_before..], &mut clicked);
+                        }
+                        if let Some(path) = clicked.open {
+                            self.request_action(PendingAction::SwitchConfig(path));
+                        }
+                        if let Some(path) = clicked.delete {
+                            self.request_action(PendingAction::RemoveConfig(path));
                         }
-                        if let Some(idx) = to_del {
-                            let removed = self.configs_paths.remove(idx);
-                            if Some(removed) == self.selected_config {
-                                self.config_data.edit_idx = None;
-                                self.config_data.edit_coord = EditCoord::None;
+                    });
+            });
+    }
+    /// Rois of the open config with the inspector of the selected one.
+    fn render_config_editor(&mut self, ui: &mut egui::Ui, delete: MouseBinding, draw_hint: &str) {
+        if let Some(img_data) = &self.img_data {
+            ui.horizontal_top(|ui| {
+                ui.label("+");
+                let button = ui.small_button("create new roi");
+                if button.clicked() {
+                    let [bx1, by1, bx2, by2] = img_data.bounds;
+                    let [x1, y1, x2, y2] = img_data.get_rel_config_coords(
+                        bx1 + 0.3 * (bx2 - bx1),
+                        by1 - 0.3 * (by1 - by2),
+                        bx2 - 0.3 * (bx2 - bx1),
+                        by2 + 0.3 * (by1 - by2),
+                    );
+                    let new_roi = JsonConfig {
+                        x1,
+                        y1,
+                        x2,
+                        y2,
+                        name: String::from("new_roi"),
+                        ..Default::default()
+                    };
+                    self.config_data.add_roi(new_roi);
+                }
+                ui.toggle_value(&mut self.config_data.draw_mode, "draw")
+                    .on_hover_text(draw_hint);
+            });
+        };
+        self.render_roi_inspector(ui);
+        ScrollArea::vertical()
+            .id_salt("editing_config_area")
+            .scroll_bar_visibility(ScrollBarVisibility::AlwaysHidden)
+            .max_height(ui.available_height() * 0.9)
+            .show(ui, |ui| {
+                let mut to_del: Option<usize> = None;
+                let mut rename_started = false;
+                let mut rename_finished = false;
+                let labels = &self.labels;
+                for (idx, c) in self.config_data.config.iter_mut().enumerate() {
+                    ui.horizontal_top(|ui| {
+                        let color = roi_color(labels, c.class());
+                        ui.colored_label(color, ">");
+                        if Some(idx) == self.config_data.edit_idx {
+                            let resp = ui.text_edit_singleline(&mut c.name);
+                            if resp.gained_focus() {
+                                rename_started = true;
+                            }
+                            if resp.lost_focus() {
+                                rename_finished = true;
+                            }
+                        } else {
+                            let button = ui.small_button(RichText::new(&c.name).color(color));
+                            if delete.clicked(&button) {
+                                to_del = Some(idx);
+                            } else if button.clicked() {
+                                self.config_data.edit_idx = Some(idx);
                             };
+                        };
+                        if is_unknown_label(labels, c.class()) {
+                            ui.colored_label(ui.visuals().warn_fg_color, "⚠")
+                                .on_hover_text("Not in the labels");
                         }
                     });
+                }
+                if rename_started {
+                    self.config_data.history.begin(&self.config_data.config);
+                }
+                if rename_finished {
+                    self.config_data.history.finish(&self.config_data.config);
+                }
+                if let Some(del_idx) = to_del {
+                    self.config_data.safely_remove_roi(del_idx);
+                }
             });
     }
 }
+
+/// Config picked in the list this frame, acted on once the list is drawn.
+#[derive(Default)]
+struct ConfigClicks {
+    open: Option<PathBuf>,
+    delete: Option<PathBuf>,
+}
+/// Draws config file names as selectable labels.
+struct ConfigNames<'a> {
+    selected: Option<&'a PathBuf>,
+    /// Whether the selected config has unsaved changes.
+    dirty: bool,
+    delete: MouseBinding,
+}
+impl ConfigNames<'_> {
+    fn render(&self, ui: &mut egui::Ui, configs_paths: &[PathBuf], clicked: &mut ConfigClicks) {
+        for config_path in configs_paths {
+            let Some(name) = config_path.file_name().map(|name| name.to_string_lossy()) else {
+                continue;
+            };
+            let is_selected = self.selected == Some(config_path);
+            let label = if is_selected && self.dirty {
+                format!("{} *", name)
+            } else {
+                name.to_string()
+            };
+            let resp = ui
+                .selectable_label(is_selected, label)
+                .on_hover_text(config_path.to_string_lossy());
+
+            if self.delete.clicked(&resp) {
+                clicked.delete = Some(config_path.to_path_buf());
+            } else if resp.clicked() {
+                clicked.open = Some(config_path.to_path_buf());
+            };
+        }
+    }
+}
@@ -0,0 +1,388 @@
+use crate::app::RoIApp;
+use crate::config::{AttrValue, JsonConfig};
+use crate::labels::{is_unknown_label, label_picker};
+use egui::{ComboBox, DragValue, Grid, Response, TextEdit, Ui};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Copy)]
+pub enum Units {
+    #[default]
+    Pixels,
+    Relative,
+}
+
+#[derive(Clone, Copy)]
+enum Axis {
+    X,
+    Y,
+}
+#[derive(Default, PartialEq, Clone, Copy)]
+enum AttrKind {
+    #[default]
+    Text,
+    Number,
+    Bool,
+    Enum,
+}
+impl AttrKind {
+    const ALL: [Self; 4] = [Self::Text, Self::Number, Self::Bool, Self::Enum];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Number => "number",
+            Self::Bool => "bool",
+            Self::Enum => "enum",
+        }
+    }
+}
+
+/// Attribute about to be added to the selected roi.
+#[derive(Default)]
+pub struct NewAttribute {
+    key: String,
+    kind: AttrKind,
+    /// Comma separated options of an enum.
+    options: String,
+}
+impl NewAttribute {
+    fn value(&self) -> Option<AttrValue> {
+        Some(match self.kind {
+            AttrKind::Text => AttrValue::String(String::new()),
+            AttrKind::Number => AttrValue::Number(0.0),
+            AttrKind::Bool => AttrValue::Bool(false),
+            AttrKind::Enum => {
+                let options: Vec<String> = self
+                    .options
+                    .split(',')
+                    .map(|option| option.trim().to_string())
+                    .filter(|option| !option.is_empty())
+                    .collect();
+                AttrValue::Enum {
+                    value: options.first()?.to_string(),
+                    options,
+                }
+            }
+        })
+    }
+}
+
+/// Which part of a `[start, end]` span a field edits.
+#[derive(Clone, Copy)]
+enum Part {
+    Start,
+    End,
+    Size,
+    Center,
+}
+
+/// Inspector rows, two fields each.
+const FIELDS: [[(&str, Axis, Part); 2]; 4] = [
+    [("x1", Axis::X, Part::Start), ("y1", Axis::Y, Part::Start)],
+    [("x2", Axis::X, Part::End), ("y2", Axis::Y, Part::End)],
+    [
+        ("width", Axis::X, Part::Size),
+        ("height", Axis::Y, Part::Size),
+    ],
+    [
+        ("center x", Axis::X, Part::Center),
+        ("center y", Axis::Y, Part::Center),
+    ],
+];
+
+fn span_mut(roi: &mut JsonConfig, axis: Axis) -> (&mut f64, &mut f64) {
+    match axis {
+        Axis::X => (&mut roi.x1, &mut roi.x2),
+        Axis::Y => (&mut roi.y1, &mut roi.y2),
+    }
+}
+fn span(roi: &JsonConfig, axis: Axis) -> [f64; 2] {
+    match axis {
+        Axis::X => [roi.x1, roi.x2],
+        Axis::Y => [roi.y1, roi.y2],
+    }
+}
+fn part_value([start, end]: [f64; 2], part: Part) -> f64 {
+    match part {
+        Part::Start => start,
+        Part::End => end,
+        Part::Size => end - start,
+        Part::Center => (start + end) / 2.0,
+    }
+}
+/// Sets a part of the span in relative units, keeping `0 <= start <= end <= 1`.
+/// Width and height keep the start, the center keeps the size.
+fn set_part(start: &mut f64, end: &mut f64, part: Part, value: f64) {
+    *start = start.clamp(0.0, 1.0);
+    *end = end.clamp(*start, 1.0);
+    match part {
+        Part::Start => *start = value.clamp(0.0, *end),
+        Part::End => *end = value.clamp(*start, 1.0),
+        Part::Size => *end = (*start + value.max(0.0)).min(1.0),
+        Part::Center => {
+            let half = (*end - *start) / 2.0;
+            let center = value.clamp(half, 1.0 - half);
+            *start = center - half;
+            *end = center + half;
+        }
+    }
+}
+
+/// Whether a widget started, continues or finished a continuous edit like a drag or typing.
+#[derive(Default)]
+struct EditState {
+    started: bool,
+    finished: bool,
+    ongoing: bool,
+}
+impl EditState {
+    fn track(&mut self, resp: &Response) {
+        self.started |= resp.drag_started() || resp.gained_focus();
+        self.finished |= resp.drag_stopped() || resp.lost_focus();
+        self.ongoing |= resp.dragged() || resp.has_focus();
+    }
+}
+
+impl RoIApp {
+    /// Editor of the selected roi: label, coords and attributes.
+    pub fn render_roi_inspector(&mut self, ui: &mut Ui) {
+        let Some(idx) = self.config_data.edit_idx else {
+            return;
+        };
+        let Some(before) = self.config_data.config.get(idx) else {
+            return;
+        };
+        let img_size = self
+            .img_data
+            .as_ref()
+            .map(|img_data| [img_data.width as f64, img_data.height as f64]);
+        // pixels need an image, the preference stays for the next one
+        let shown_units = match img_size {
+            Some(_) => self.roi_units,
+            None => Units::Relative,
+        };
+        let mut units = shown_units;
+        // widgets edit a copy, so the history can snapshot the state before the edit
+        let mut roi = before.clone();
+        let mut edit = EditState::default();
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("id {}", roi.id));
+                ui.label("label:");
+                let resp = ui.add(
+                    TextEdit::singleline(&mut roi.label)
+                        .hint_text(&roi.name)
+                        .desired_width(100.0),
+                );
+                edit.track(&resp);
+                if let Some(label) = label_picker(ui, &self.labels, idx, roi.class()) {
+                    roi.label = label;
+                }
+            });
+            if is_unknown_label(&self.labels, roi.class()) {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("{:?} is not in the labels", roi.class()),
+                );
+            }
+
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(img_size.is_some(), |ui| {
+                    ui.selectable_value(&mut units, Units::Pixels, "px");
+                });
+                ui.selectable_value(&mut units, Units::Relative, "0-1");
+            });
+            let [scale_x, scale_y] = match (units, img_size) {
+                (Units::Pixels, Some(size)) => size,
+                _ => [1.0, 1.0],
+            };
+            let (speed, decimals) = match units {
+                Units::Pixels => (1.0, 1),
+                Units::Relative => (0.001, 4),
+            };
+            Grid::new("roi_inspector_grid")
+                .num_columns(4)
+                .show(ui, |ui| {
+                    for row in FIELDS {
+                        for (label, axis, part) in row {
+                            let scale = match axis {
+                                Axis::X => scale_x,
+                                Axis::Y => scale_y,
+                            };
+                            let mut value = part_value(span(&roi, axis), part) * scale;
+                            ui.label(label);
+                            let resp = ui.add(
+                                DragValue::new(&mut value)
+                                    .range(0.0..=scale)
+                                    .speed(speed)
+                                    .max_decimals(decimals),
+                            );
+                            if resp.changed() {
+                                let (start, end) = span_mut(&mut roi, axis);
+                                set_part(start, end, part, value / scale);
+                            }
+                            edit.track(&resp);
+                        }
+                        ui.end_row();
+                    }
+                    ui.label("angle");
+                    let resp = ui
+                        .add(
+                            DragValue::new(&mut roi.angle)
+                                .range(-180.0..=180.0)
+                                .speed(0.5)
+                                .max_decimals(1)
+                                .suffix("°"),
+                        )
+                        .on_hover_text("Clockwise around the center");
+                    edit.track(&resp);
+                    ui.end_row();
+                });
+            if let Err(err) = roi.validate() {
+                ui.colored_label(ui.visuals().warn_fg_color, format!("{:#}", err));
+            }
+
+            ui.separator();
+            let mut to_remove: Option<String> = None;
+            for (key, value) in roi.attributes.iter_mut() {
+                ui.horizontal(|ui| {
+                    if ui.small_button("x").on_hover_text("Remove").clicked() {
+                        to_remove = Some(key.to_string());
+                    }
+                    ui.label(key);
+                    let resp = match value {
+                        AttrValue::Bool(value) => ui.checkbox(value, ""),
+                        AttrValue::Number(value) => ui.add(DragValue::new(value).speed(0.1)),
+                        AttrValue::String(value) => ui.text_edit_singleline(value),
+                        AttrValue::Enum { value, options } => {
+                            ComboBox::from_id_salt(("roi_attribute", key))
+                                .selected_text(value.as_str())
+                                .show_ui(ui, |ui| {
+                                    for option in options.iter() {
+                                        ui.selectable_value(value, option.to_string(), option);
+                                    }
+                                })
+                                .response
+                        }
+                    };
+                    edit.track(&resp);
+                });
+            }
+            if let Some(key) = to_remove {
+                roi.attributes.remove(&key);
+            }
+            let new_attribute = &mut self.new_attribute;
+            ui.horizontal(|ui| {
+                ui.add(
+                    TextEdit::singleline(&mut new_attribute.key)
+                        .hint_text("attribute")
+                        .desired_width(80.0),
+                );
+                ComboBox::from_id_salt("new_attribute_kind")
+                    .selected_text(new_attribute.kind.label())
+                    .show_ui(ui, |ui| {
+                        for kind in AttrKind::ALL {
+                            ui.selectable_value(&mut new_attribute.kind, kind, kind.label());
+                        }
+                    });
+                if new_attribute.kind == AttrKind::Enum {
+                    ui.add(
+                        TextEdit::singleline(&mut new_attribute.options)
+                            .hint_text("a, b, c")
+                            .desired_width(80.0),
+                    );
+                }
+                let key = new_attribute.key.trim();
+                let value = new_attribute.value();
+                let can_add =
+                    !key.is_empty() && !roi.attributes.contains_key(key) && value.is_some();
+                if ui.add_enabled(can_add, egui::Button::new("Add")).clicked() {
+                    if let Some(value) = value {
+                        roi.attributes.insert(key.to_string(), value);
+                        new_attribute.key.clear();
+                    }
+                }
+            });
+        });
+
+        if units != shown_units {
+            self.roi_units = units;
+        }
+        let config_data = &mut self.config_data;
+        // like dragging, the corners of a rotated roi stay in the image
+        if let Some(size) = img_size {
+            let before = &config_data.config[idx];
+            if !roi.is_inside(size) && before.is_inside(size) {
+                roi.x1 = before.x1;
+                roi.y1 = before.y1;
+                roi.x2 = before.x2;
+                roi.y2 = before.y2;
+                roi.angle = before.angle;
+            }
+        }
+        if edit.started {
+            config_data.history.begin(&config_data.config);
+        }
+        if config_data.config[idx] != roi {
+            if !edit.started && !edit.finished && !edit.ongoing {
+                config_data.history.record(&config_data.config);
+            }
+            config_data.config[idx] = roi;
+        }
+        if edit.finished {
+            config_data.history.finish(&config_data.config);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(span: [f64; 2], part: Part, value: f64) -> [f64; 2] {
+        let [mut start, mut end] = span;
+        set_part(&mut start, &mut end, part, value);
+        [start, end]
+    }
+
+    #[test]
+    fn set_part_keeps_the_span_ordered_and_in_the_image() {
+        assert_eq!(set([0.2, 0.6], Part::Start, 0.8), [0.6, 0.6]);
+        assert_eq!(set([0.2, 0.6], Part::Start, -0.5), [0.0, 0.6]);
+        assert_eq!(set([0.2, 0.6], Part::End, 0.1), [0.2, 0.2]);
+        assert_eq!(set([0.2, 0.6], Part::End, 1.5), [0.2, 1.0]);
+        assert_eq!(set([0.5, 0.75], Part::Size, 0.25), [0.5, 0.75]);
+        assert_eq!(set([0.5, 0.75], Part::Size, 0.75), [0.5, 1.0]);
+        assert_eq!(set([0.5, 0.75], Part::Size, -1.0), [0.5, 0.5]);
+        // the center keeps the size and stops at the border
+        assert_eq!(set([0.25, 0.75], Part::Center, 0.5), [0.25, 0.75]);
+        assert_eq!(set([0.25, 0.75], Part::Center, 0.9), [0.5, 1.0]);
+        assert_eq!(set([0.25, 0.75], Part::Center, 0.0), [0.0, 0.5]);
+        // spans out of range or order are repaired first
+        assert_eq!(set([0.7, 0.3], Part::Start, 0.5), [0.5, 0.7]);
+        assert_eq!(set([-0.5, 1.5], Part::Size, 0.5), [0.0, 0.5]);
+    }
+
+    #[test]
+    fn inspector_without_image_keeps_the_pixel_preference() {
+        let mut app = RoIApp {
+            roi_units: Units::Pixels,
+            ..Default::default()
+        };
+        app.config_data.reset_config(vec![JsonConfig {
+            x1: 0.25,
+            y1: 0.25,
+            x2: 0.75,
+            y2: 0.75,
+            name: String::from("cat"),
+            ..Default::default()
+        }]);
+        app.config_data.edit_idx = Some(0);
+        let ctx = egui::Context::default();
+        let _ = ctx.run(Default::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| app.render_roi_inspector(ui));
+        });
+        assert!(app.roi_units == Units::Pixels);
+        assert!(!app.config_data.is_dirty());
+    }
+}
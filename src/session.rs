@@ -0,0 +1,126 @@
+use crate::app::RoIApp;
+use crate::project::read_project;
+use eframe::Storage;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+pub const DEFAULT_UI_SCALE: f32 = 1.2;
+
+const SESSION_KEY: &str = "session";
+const UI_SCALE_KEY: &str = "ui_scale";
+const BINDINGS_KEY: &str = "bindings";
+const SCAN_SETTINGS_KEY: &str = "scan_settings";
+const EXPORT_SETTINGS_KEY: &str = "export_settings";
+const CROP_SETTINGS_KEY: &str = "crop_settings";
+const IMAGE_SETTINGS_KEY: &str = "image_settings";
+const ROI_UNITS_KEY: &str = "roi_units";
+
+/// What was open when the app was closed.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Session {
+    pub imgs_paths: Vec<PathBuf>,
+    pub configs_paths: Vec<PathBuf>,
+    pub selected_img: Option<PathBuf>,
+    pub selected_config: Option<PathBuf>,
+    pub show_all_configs: bool,
+    pub project_path: Option<PathBuf>,
+    /// Labels as edited, the project only gets them on its next save.
+    pub labels: Vec<String>,
+    /// Visible part of the image as `[x1, y1, x2, y2]` plot coords.
+    pub plot_bounds: Option<[f64; 4]>,
+}
+
+impl RoIApp {
+    /// Restores preferences and the last session. Files that are gone by now are skipped.
+    pub fn restore_state(&mut self, ctx: &egui::Context, storage: &dyn Storage) {
+        self.ui_scale = eframe::get_value(storage, UI_SCALE_KEY).unwrap_or(DEFAULT_UI_SCALE);
+        if let Some(bindings) = eframe::get_value(storage, BINDINGS_KEY) {
+            self.bindings = bindings;
+        }
+        if let Some(scan_settings) = eframe::get_value(storage, SCAN_SETTINGS_KEY) {
+            self.scan_settings = scan_settings;
+        }
+        if let Some(export_settings) = eframe::get_value(storage, EXPORT_SETTINGS_KEY) {
+            self.export_settings = export_settings;
+        }
+        if let Some(crop_settings) = eframe::get_value(storage, CROP_SETTINGS_KEY) {
+            self.crop_settings = crop_settings;
+        }
+        if let Some(image_settings) = eframe::get_value(storage, IMAGE_SETTINGS_KEY) {
+            self.image_settings = image_settings;
+        }
+        if let Some(roi_units) = eframe::get_value(storage, ROI_UNITS_KEY) {
+            self.roi_units = roi_units;
+        }
+
+        let Some(session) = eframe::get_value::<Session>(storage, SESSION_KEY) else {
+            return;
+        };
+        let n_listed = session.imgs_paths.len() + session.configs_paths.len();
+        self.imgs_paths = session
+            .imgs_paths
+            .into_iter()
+            .filter(|path| path.is_file())
+            .collect();
+        self.configs_paths = session
+            .configs_paths
+            .into_iter()
+            .filter(|path| path.is_file())
+            .collect();
+        self.configs_generation += 1;
+        let n_missing = n_listed - self.imgs_paths.len() - self.configs_paths.len();
+        if n_missing > 0 {
+            self.notifications.warn(format!(
+                "{} files of the last session no longer exist",
+                n_missing
+            ));
+        }
+        self.show_all_configs = session.show_all_configs;
+        self.project_path = session.project_path.filter(|path| path.is_file());
+        self.labels = session.labels;
+        // sessions from before labels were kept still have them in the project
+        if self.labels.is_empty() {
+            if let Some(project) = self
+                .project_path
+                .as_deref()
+                .and_then(|p| read_project(p).ok())
+            {
+                self.labels = project.labels;
+            }
+        }
+
+        if let Some(img_path) = session.selected_img {
+            if self.imgs_paths.contains(&img_path) {
+                self.open_image(ctx, img_path);
+                self.restore_bounds = session.plot_bounds;
+            }
+        }
+        if let Some(config_path) = session.selected_config {
+            if self.configs_paths.contains(&config_path) {
+                self.open_config(config_path);
+            }
+        }
+    }
+    pub fn save_state(&self, storage: &mut dyn Storage) {
+        eframe::set_value(storage, UI_SCALE_KEY, &self.ui_scale);
+        eframe::set_value(storage, BINDINGS_KEY, &self.bindings);
+        eframe::set_value(storage, SCAN_SETTINGS_KEY, &self.scan_settings);
+        eframe::set_value(storage, EXPORT_SETTINGS_KEY, &self.export_settings);
+        eframe::set_value(storage, CROP_SETTINGS_KEY, &self.crop_settings);
+        eframe::set_value(storage, IMAGE_SETTINGS_KEY, &self.image_settings);
+        eframe::set_value(storage, ROI_UNITS_KEY, &self.roi_units);
+
+        let session = Session {
+            imgs_paths: self.imgs_paths.clone(),
+            configs_paths: self.configs_paths.clone(),
+            selected_img: self.selected_img.clone(),
+            selected_config: self.selected_config.clone(),
+            show_all_configs: self.show_all_configs,
+            project_path: self.project_path.clone(),
+            labels: self.labels.clone(),
+            plot_bounds: self.img_data.as_ref().map(|img_data| img_data.bounds),
+        };
+        eframe::set_value(storage, SESSION_KEY, &session);
+    }
+}
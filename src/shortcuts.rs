@@ -0,0 +1,199 @@
+use crate::app::RoIApp;
+use crate::bindings::MouseAction;
+use crate::unsaved_changes::PendingAction;
+use egui::{Grid, Window};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Action {
+    Undo,
+    Redo,
+    NextImage,
+    PrevImage,
+    NextConfig,
+    PrevConfig,
+    NextRoi,
+    PrevRoi,
+    DeleteRoi,
+    Save,
+    ToggleDraw,
+    CancelEdit,
+    ToggleHelp,
+}
+impl Action {
+    pub const ALL: [Self; 13] = [
+        Self::Undo,
+        Self::Redo,
+        Self::NextImage,
+        Self::PrevImage,
+        Self::NextConfig,
+        Self::PrevConfig,
+        Self::NextRoi,
+        Self::PrevRoi,
+        Self::DeleteRoi,
+        Self::Save,
+        Self::ToggleDraw,
+        Self::CancelEdit,
+        Self::ToggleHelp,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Undo => "undo bbox change",
+            Self::Redo => "redo bbox change",
+            Self::NextImage => "next image",
+            Self::PrevImage => "previous image",
+            Self::NextConfig => "next config",
+            Self::PrevConfig => "previous config",
+            Self::NextRoi => "select next bbox",
+            Self::PrevRoi => "select previous bbox",
+            Self::DeleteRoi => "delete selected bbox",
+            Self::Save => "save current config",
+            Self::ToggleDraw => "toggle draw mode",
+            Self::CancelEdit => "cancel drawing or editing",
+            Self::ToggleHelp => "show this help",
+        }
+    }
+}
+
+/// Index of the item next to `current` among `len` items, in the given direction.
+/// Without `wrap` it stops at the ends, with nothing selected it starts from one of them.
+fn step(len: usize, current: Option<usize>, forward: bool, wrap: bool) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    let last = len - 1;
+    Some(match (current, forward) {
+        (None, true) => 0,
+        (None, false) => last,
+        (Some(idx), true) if idx >= last => {
+            if wrap {
+                0
+            } else {
+                last
+            }
+        }
+        (Some(idx), true) => idx + 1,
+        (Some(0), false) => {
+            if wrap {
+                last
+            } else {
+                0
+            }
+        }
+        (Some(idx), false) => idx - 1,
+    })
+}
+
+impl RoIApp {
+    pub fn process_shortcuts(&mut self, ctx: &egui::Context) {
+        // let text fields handle their own keys, undo included
+        if ctx.wants_keyboard_input()
+            || self.pending_action.is_some()
+            || self.bindings_settings.recording.is_some()
+        {
+            return;
+        }
+        let keys = self.bindings.ordered_keys();
+        let actions: Vec<Action> = ctx.input_mut(|i| {
+            keys.iter()
+                .filter(|(_, shortcut)| i.consume_shortcut(shortcut))
+                .map(|(action, _)| *action)
+                .collect()
+        });
+        for action in actions {
+            self.run_shortcut(ctx, action);
+        }
+    }
+    fn run_shortcut(&mut self, ctx: &egui::Context, action: Action) {
+        match action {
+            Action::Undo => self.config_data.undo(),
+            Action::Redo => self.config_data.redo(),
+            Action::NextImage | Action::PrevImage => {
+                let current = self
+                    .selected_img
+                    .as_ref()
+                    .and_then(|path| self.imgs_paths.iter().position(|p| p == path));
+                let forward = action == Action::NextImage;
+                if let Some(idx) = step(self.imgs_paths.len(), current, forward, false) {
+                    if Some(idx) != current {
+                        self.open_image(ctx, self.imgs_paths[idx].to_path_buf());
+                    }
+                }
+            }
+            Action::NextConfig | Action::PrevConfig => {
+                let selected = self.selected_config.clone();
+                let listed = self.listed_configs();
+                let current = selected.and_then(|path| listed.iter().position(|p| p == &path));
+                let forward = action == Action::NextConfig;
+                if let Some(idx) = step(listed.len(), current, forward, false) {
+                    if Some(idx) != current {
+                        let path = listed[idx].to_path_buf();
+                        self.request_action(PendingAction::SwitchConfig(path));
+                    }
+                }
+            }
+            Action::NextRoi | Action::PrevRoi => {
+                let config_data = &mut self.config_data;
+                let forward = action == Action::NextRoi;
+                config_data.edit_idx = step(
+                    config_data.config.len(),
+                    config_data.edit_idx,
+                    forward,
+                    true,
+                );
+                config_data.edit_coord = Default::default();
+            }
+            Action::DeleteRoi => {
+                if let Some(idx) = self.config_data.edit_idx {
+                    self.config_data.safely_remove_roi(idx);
+                }
+            }
+            Action::Save => {
+                self.save_current_config();
+            }
+            Action::ToggleDraw => {
+                self.config_data.draw_mode = !self.config_data.draw_mode;
+                self.config_data.draw_start = None;
+            }
+            Action::CancelEdit => {
+                if self.help_open {
+                    self.help_open = false;
+                } else {
+                    self.config_data.cancel_edit();
+                }
+            }
+            Action::ToggleHelp => self.help_open = !self.help_open,
+        }
+    }
+    pub fn render_help_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.help_open;
+        let mut edit_clicked = false;
+        Window::new("Shortcuts")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                Grid::new("shortcuts_grid")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for action in Action::ALL {
+                            ui.monospace(self.bindings.format_keys(ctx, action));
+                            ui.label(action.label());
+                            ui.end_row();
+                        }
+                        for action in MouseAction::ALL {
+                            ui.monospace(self.bindings.mouse(action).format(ctx));
+                            ui.label(action.label());
+                            ui.end_row();
+                        }
+                    });
+                edit_clicked = ui.button("Edit bindings").clicked();
+            });
+        self.help_open = open;
+        if edit_clicked {
+            self.bindings_settings.open = true;
+        }
+    }
+}
@@ -1,23 +1,54 @@
 use crate::app::RoIApp;
-use std::fs;
+use egui::{Align, DragValue, Layout};
 
 impl RoIApp {
     pub fn render_top_side_panel(&mut self, ctx: &egui::Context) {
         egui::TopBottomPanel::top("top panel").show(ctx, |ui| {
-            ui.vertical_centered(|ui| {
-                if let Some(path) = &self.selected_config {
-                    if ui.button("Save current config").clicked() {
-                        if let Ok(json_string) =
-                            serde_json::to_string_pretty(&self.config_data.config)
-                        {
-                            if fs::write(path, &json_string).is_ok() {
-                                println!("Saved {}", &path.display());
-                            } else {
-                                eprintln!("Failed saving {}", &path.display());
-                            };
-                        };
-                    };
+            ui.horizontal(|ui| {
+                if self.selected_config.is_some() && ui.button("Save current config").clicked() {
+                    self.save_current_config();
                 }
+                if ui.button("Project").clicked() {
+                    self.project_window.open = !self.project_window.open;
+                }
+                if ui.button("Labels").clicked() {
+                    self.labels_window.open = !self.labels_window.open;
+                }
+                if ui.button("Export").clicked() {
+                    self.export_settings.open = !self.export_settings.open;
+                }
+                if ui.button("Export crops").clicked() {
+                    self.crop_settings.open = !self.crop_settings.open;
+                }
+                if ui.button("Folder scan settings").clicked() {
+                    self.scan_settings_open = !self.scan_settings_open;
+                }
+                if ui.button("Image settings").clicked() {
+                    self.image_settings.open = !self.image_settings.open;
+                }
+                if ui.button("Shortcuts").clicked() {
+                    self.help_open = !self.help_open;
+                }
+                let n_errors = self.notifications.n_errors();
+                let log_label = if n_errors > 0 {
+                    format!("Log ({} errors)", n_errors)
+                } else {
+                    String::from("Log")
+                };
+                if ui.button(log_label).clicked() {
+                    self.notifications.log_open = !self.notifications.log_open;
+                }
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    // also follows zooming with Ctrl +/-
+                    self.ui_scale = ctx.pixels_per_point();
+                    let scale = DragValue::new(&mut self.ui_scale)
+                        .range(0.5..=3.0)
+                        .speed(0.01)
+                        .prefix("UI scale: ");
+                    if ui.add(scale).changed() {
+                        ctx.set_pixels_per_point(self.ui_scale);
+                    }
+                });
             });
         });
     }
@@ -0,0 +1,103 @@
+use crate::app::RoIApp;
+use egui::{Id, Modal, ViewportCommand};
+use std::path::PathBuf;
+
+/// Action waiting for the user to decide what to do with unsaved changes.
+pub enum PendingAction {
+    SwitchConfig(PathBuf),
+    CloseConfig,
+    RemoveConfig(PathBuf),
+    OpenProject(PathBuf),
+    Close,
+}
+
+impl RoIApp {
+    /// Runs the action right away, or asks about unsaved changes first.
+    pub fn request_action(&mut self, action: PendingAction) {
+        let affects_current = match &action {
+            PendingAction::SwitchConfig(_)
+            | PendingAction::CloseConfig
+            | PendingAction::OpenProject(_)
+            | PendingAction::Close => true,
+            PendingAction::RemoveConfig(path) => self.selected_config.as_ref() == Some(path),
+        };
+        if affects_current && self.config_data.is_dirty() {
+            self.pending_action = Some(action);
+        } else {
+            self.run_action(action);
+        }
+    }
+    fn run_action(&mut self, action: PendingAction) {
+        match action {
+            PendingAction::SwitchConfig(path) => self.open_config(path),
+            PendingAction::CloseConfig => self.close_config(),
+            PendingAction::RemoveConfig(path) => self.remove_config(&path),
+            PendingAction::OpenProject(path) => match self.open_project(path.to_path_buf()) {
+                Ok(()) => self
+                    .notifications
+                    .info(format!("Opened {}", path.display())),
+                Err(err) => self
+                    .notifications
+                    .error(format!("Opening project failed: {:#}", err)),
+            },
+            PendingAction::Close => {}
+        }
+    }
+    /// Intercepts closing the window while there are unsaved changes.
+    pub fn process_close_request(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.viewport().close_requested()) && self.config_data.is_dirty() {
+            ctx.send_viewport_cmd(ViewportCommand::CancelClose);
+            self.pending_action = Some(PendingAction::Close);
+        }
+    }
+    pub fn render_unsaved_changes_dialog(&mut self, ctx: &egui::Context) {
+        if self.pending_action.is_none() {
+            return;
+        }
+        let name = self
+            .selected_config
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let mut save = false;
+        let mut discard = false;
+        let mut cancel = false;
+        let modal = Modal::new(Id::new("unsaved_changes_modal")).show(ctx, |ui| {
+            ui.heading("Unsaved changes");
+            ui.label(format!("Save changes to {}?", name));
+            ui.separator();
+            ui.horizontal(|ui| {
+                save = ui.button("Save").clicked();
+                discard = ui.button("Discard").clicked();
+                cancel = ui.button("Cancel").clicked();
+            });
+        });
+        if modal.should_close() {
+            cancel = true;
+        }
+
+        if cancel {
+            self.pending_action = None;
+            return;
+        }
+        if save {
+            // keep the dialog open if saving failed, so nothing gets lost
+            if !self.save_current_config() {
+                return;
+            }
+        } else if discard {
+            self.config_data.discard_changes();
+        } else {
+            return;
+        }
+
+        if let Some(action) = self.pending_action.take() {
+            if matches!(action, PendingAction::Close) {
+                ctx.send_viewport_cmd(ViewportCommand::Close);
+            }
+            self.run_action(action);
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use crate::config::{bounding_boxes, JsonConfig};
+use crate::dataset::IMAGE_EXTENSIONS;
+use crate::export::check_unique_stems;
+use crate::orientation::{image_size, RoiFrame};
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Pascal VOC annotation of a single image.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "annotation")]
+pub struct VocAnnotation {
+    #[serde(default)]
+    pub folder: String,
+    #[serde(default)]
+    pub filename: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub size: Option<VocSize>,
+    #[serde(default, rename = "object")]
+    pub objects: Vec<VocObject>,
+}
+#[derive(Serialize, Deserialize)]
+pub struct VocSize {
+    pub width: usize,
+    pub height: usize,
+    #[serde(default = "default_depth")]
+    pub depth: usize,
+}
+#[derive(Serialize, Deserialize)]
+pub struct VocObject {
+    pub name: String,
+    #[serde(default = "default_pose")]
+    pub pose: String,
+    #[serde(default)]
+    pub truncated: u8,
+    #[serde(default)]
+    pub difficult: u8,
+    pub bndbox: VocBndBox,
+}
+/// Pixel coords, treated as box edges. Written as integers, but fractional values are accepted.
+#[derive(Serialize, Deserialize)]
+pub struct VocBndBox {
+    pub xmin: f64,
+    pub ymin: f64,
+    pub xmax: f64,
+    pub ymax: f64,
+}
+fn default_depth() -> usize {
+    3
+}
+fn default_pose() -> String {
+    String::from("Unspecified")
+}
+
+/// Builds a VOC annotation for an image of the given `[width, height]`.
+pub fn to_voc(img_path: &Path, img_size: [usize; 2], config: &[JsonConfig]) -> VocAnnotation {
+    let [width, height] = img_size;
+    let objects = config
+        .iter()
+        .map(|roi| {
+            let [xmin, ymin, xmax, ymax] = [
+                (roi.x1 * width as f64).floor(),
+                (roi.y1 * height as f64).floor(),
+                (roi.x2 * width as f64).ceil(),
+                (roi.y2 * height as f64).ceil(),
+            ];
+            VocObject {
+                name: roi.class().to_string(),
+                pose: default_pose(),
+                truncated: 0,
+                difficult: 0,
+                bndbox: VocBndBox {
+                    xmin,
+                    ymin,
+                    xmax,
+                    ymax,
+                },
+            }
+        })
+        .collect();
+    let name_of = |path: Option<&Path>| {
+        path.and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+    VocAnnotation {
+        folder: name_of(img_path.parent()),
+        filename: name_of(Some(img_path)),
+        path: Some(
+            fs::canonicalize(img_path)
+                .unwrap_or_else(|_| img_path.to_path_buf())
+                .to_string_lossy()
+                .to_string(),
+        ),
+        size: Some(VocSize {
+            width,
+            height,
+            depth: default_depth(),
+        }),
+        objects,
+    }
+}
+/// Converts VOC objects to rois in an image of the given `[width, height]`.
+pub fn from_voc(annotation: &VocAnnotation, img_size: [usize; 2]) -> Vec<JsonConfig> {
+    let [width, height] = img_size.map(|val| val as f64);
+    annotation
+        .objects
+        .iter()
+        .map(|object| {
+            let bndbox = &object.bndbox;
+            JsonConfig {
+                x1: (bndbox.xmin.min(bndbox.xmax) / width).clamp(0.0, 1.0),
+                y1: (bndbox.ymin.min(bndbox.ymax) / height).clamp(0.0, 1.0),
+                x2: (bndbox.xmax.max(bndbox.xmin) / width).clamp(0.0, 1.0),
+                y2: (bndbox.ymax.max(bndbox.ymin) / height).clamp(0.0, 1.0),
+                name: object.name.clone(),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+pub fn read_voc(path: &Path) -> anyhow::Result<VocAnnotation> {
+    let xml_string =
+        fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    quick_xml::de::from_str(&xml_string).with_context(|| format!("parsing {}", path.display()))
+}
+pub fn write_voc(path: &Path, annotation: &VocAnnotation) -> anyhow::Result<()> {
+    let mut xml_string = String::new();
+    let mut serializer = quick_xml::se::Serializer::new(&mut xml_string);
+    serializer.indent(' ', 4);
+    annotation.serialize(serializer)?;
+    xml_string.push('\n');
+    fs::write(path, xml_string).with_context(|| format!("writing {}", path.display()))
+}
+
+/// Finds the image a VOC file refers to: its `path`, its `filename` next to the xml
+/// or in a sibling `JPEGImages` dir as in VOC devkit, or any supported image with the xml file stem.
+fn find_voc_image(path: &Path, annotation: &VocAnnotation) -> Option<PathBuf> {
+    if !annotation.filename.is_empty() {
+        let next_to_xml = path.with_file_name(&annotation.filename);
+        let candidates = [
+            annotation.path.as_ref().map(PathBuf::from),
+            Some(next_to_xml.to_path_buf()),
+            path.parent()
+                .and_then(|dir| dir.parent())
+                .map(|dir| dir.join("JPEGImages").join(&annotation.filename)),
+        ];
+        return candidates
+            .into_iter()
+            .flatten()
+            .find(|img_path| img_path.is_file())
+            .or(Some(next_to_xml));
+    }
+    IMAGE_EXTENSIONS
+        .into_iter()
+        .map(|ext| path.with_extension(ext))
+        .find(|img_path| img_path.is_file())
+}
+/// Reads a VOC file into rois of its image.
+/// The image size is taken from the xml, or else from `size_of` the image.
+pub fn import_voc(
+    path: &Path,
+    size_of: impl Fn(&Path) -> Option<[usize; 2]>,
+) -> anyhow::Result<(PathBuf, Vec<JsonConfig>)> {
+    let annotation = read_voc(path)?;
+    let Some(img_path) = find_voc_image(path, &annotation) else {
+        bail!("no image found for {}", path.display());
+    };
+    let img_size = match &annotation.size {
+        Some(size) if size.width > 0 && size.height > 0 => [size.width, size.height],
+        _ => {
+            size_of(&img_path).with_context(|| format!("unknown size of {}", img_path.display()))?
+        }
+    };
+    let config = from_voc(&annotation, img_size);
+    Ok((img_path, config))
+}
+/// Writes `<image stem>.xml` for every image into `out_dir`,
+/// reading image sizes from the files in the `frame` the rois refer to.
+pub fn export_voc(
+    items: &[(PathBuf, Vec<JsonConfig>)],
+    out_dir: &Path,
+    frame: RoiFrame,
+) -> anyhow::Result<()> {
+    check_unique_stems(items)?;
+    fs::create_dir_all(out_dir).with_context(|| format!("creating {}", out_dir.display()))?;
+    for (img_path, config) in items {
+        let Some(stem) = img_path.file_stem() else {
+            continue;
+        };
+        let [width, height] = image_size(img_path, frame)?;
+        let config = bounding_boxes(config, img_path, frame)?;
+        let annotation = to_voc(img_path, [width as usize, height as usize], &config);
+        write_voc(
+            &out_dir.join(format!("{}.xml", stem.to_string_lossy())),
+            &annotation,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{test_dir, write_png};
+
+    #[test]
+    fn export_uses_the_oriented_size() {
+        let dir = test_dir("voc_oriented");
+        let img_path = dir.join("img.png");
+        write_png(&img_path, [4, 2], 6);
+        let roi = JsonConfig {
+            x1: 0.5,
+            x2: 1.0,
+            y2: 0.5,
+            name: String::from("cat"),
+            ..Default::default()
+        };
+        export_voc(&[(img_path, vec![roi])], &dir, RoiFrame::Oriented).unwrap();
+
+        let annotation = read_voc(&dir.join("img.xml")).unwrap();
+        let size = annotation.size.unwrap();
+        assert_eq!([size.width, size.height], [2, 4]);
+        let bndbox = &annotation.objects[0].bndbox;
+        assert_eq!(
+            [bndbox.xmin, bndbox.ymin, bndbox.xmax, bndbox.ymax],
+            [1.0, 0.0, 2.0, 2.0]
+        );
+    }
+
+    #[test]
+    fn write_and_import_round_trip() {
+        let dir = test_dir("voc_round_trip");
+        let img_path = dir.join("img.png");
+        write_png(&img_path, [10, 20], 1);
+        let config = vec![
+            JsonConfig {
+                x1: 0.1,
+                y1: 0.25,
+                x2: 0.5,
+                y2: 0.75,
+                name: String::from("cat"),
+                ..Default::default()
+            },
+            JsonConfig {
+                x2: 1.0,
+                y2: 1.0,
+                name: String::from("dog"),
+                ..Default::default()
+            },
+        ];
+        let xml_path = dir.join("img.xml");
+        write_voc(&xml_path, &to_voc(&img_path, [10, 20], &config)).unwrap();
+
+        let annotation = read_voc(&xml_path).unwrap();
+        assert_eq!(annotation.filename, "img.png");
+        assert_eq!(annotation.objects[0].pose, "Unspecified");
+        let (imported_path, imported) = import_voc(&xml_path, |_| None).unwrap();
+        assert_eq!(imported_path, fs::canonicalize(&img_path).unwrap());
+        for (imported, roi) in imported.iter().zip(&config) {
+            assert_eq!(imported.name, roi.name);
+            for (a, b) in [
+                (imported.x1, roi.x1),
+                (imported.y1, roi.y1),
+                (imported.x2, roi.x2),
+                (imported.y2, roi.y2),
+            ] {
+                assert!((a - b).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn to_voc_covers_fractional_pixels() {
+        let roi = JsonConfig {
+            x1: 0.15,
+            y1: 0.15,
+            x2: 0.55,
+            y2: 0.55,
+            ..Default::default()
+        };
+        let annotation = to_voc(Path::new("img.png"), [10, 10], &[roi]);
+        let bndbox = &annotation.objects[0].bndbox;
+        assert_eq!(
+            [bndbox.xmin, bndbox.ymin, bndbox.xmax, bndbox.ymax],
+            [1.0, 1.0, 6.0, 6.0]
+        );
+    }
+
+    #[test]
+    fn import_reads_minimal_xml_with_swapped_edges() {
+        let dir = test_dir("voc_minimal");
+        write_png(&dir.join("img.png"), [10, 20], 1);
+        let xml_path = dir.join("img.xml");
+        fs::write(
+            &xml_path,
+            "<annotation><filename>img.png</filename><object><name>cat</name>\
+             <bndbox><xmin>8</xmin><ymin>2.5</ymin><xmax>2</xmax><ymax>30</ymax></bndbox>\
+             </object></annotation>",
+        )
+        .unwrap();
+
+        let (img_path, config) = import_voc(&xml_path, |_| Some([10, 20])).unwrap();
+        assert_eq!(img_path, dir.join("img.png"));
+        assert_eq!(config[0].name, "cat");
+        assert_eq!(
+            [config[0].x1, config[0].y1, config[0].x2, config[0].y2],
+            [0.2, 0.125, 0.8, 1.0]
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use egui::{Color32, ColorImage};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+/// Share of the darkest and of the brightest samples clipped by the auto window.
+const AUTO_CLIP: f64 = 0.005;
+const HISTOGRAM_BINS: usize = 4096;
+
+/// Maps 16-bit and float samples to displayable 8-bit ones.
+/// Values are fractions of the full sample range, the way `to_rgba32f` normalizes them.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(default)]
+pub struct WindowLevel {
+    /// Pick the window from the image histogram.
+    pub auto: bool,
+    pub low: f32,
+    pub high: f32,
+}
+impl Default for WindowLevel {
+    fn default() -> Self {
+        Self {
+            auto: true,
+            low: 0.0,
+            high: 1.0,
+        }
+    }
+}
+impl WindowLevel {
+    /// Converts an image to a texture, windowing samples deeper than 8 bits.
+    pub fn to_color_image(self, img: &DynamicImage) -> ColorImage {
+        let size = [img.width() as usize, img.height() as usize];
+        if !is_high_bit_depth(img) {
+            return ColorImage::from_rgba_unmultiplied(size, img.to_rgba8().as_raw());
+        }
+        let rgba = img.to_rgba32f();
+        let [low, high] = if self.auto {
+            auto_window(&rgba)
+        } else {
+            [self.low, self.high]
+        };
+        let scale = 255.0 / (high - low).max(f32::EPSILON);
+        let map = |val: f32| ((val - low) * scale).clamp(0.0, 255.0) as u8;
+        let pixels = rgba
+            .pixels()
+            .map(|pixel| {
+                let [r, g, b, a] = pixel.0;
+                let alpha = (a.clamp(0.0, 1.0) * 255.0) as u8;
+                Color32::from_rgba_unmultiplied(map(r), map(g), map(b), alpha)
+            })
+            .collect();
+        ColorImage { size, pixels }
+    }
+}
+
+pub fn is_high_bit_depth(img: &DynamicImage) -> bool {
+    !matches!(
+        img,
+        DynamicImage::ImageLuma8(_)
+            | DynamicImage::ImageLumaA8(_)
+            | DynamicImage::ImageRgb8(_)
+            | DynamicImage::ImageRgba8(_)
+    )
+}
+
+/// Window clipping `AUTO_CLIP` of the color samples on both ends.
+fn auto_window(rgba: &image::Rgba32FImage) -> [f32; 2] {
+    let colors = || {
+        rgba.pixels()
+            .flat_map(|pixel| [pixel.0[0], pixel.0[1], pixel.0[2]])
+    };
+    let (min, max) = colors().fold((f32::MAX, f32::MIN), |(min, max), val| {
+        (min.min(val), max.max(val))
+    });
+    if min >= max {
+        return [min, min + 1.0];
+    }
+    let bin_of =
+        |val: f32| (((val - min) / (max - min)) * (HISTOGRAM_BINS - 1) as f32).round() as usize;
+    let mut histogram = vec![0usize; HISTOGRAM_BINS];
+    for val in colors() {
+        histogram[bin_of(val)] += 1;
+    }
+    let total: usize = histogram.iter().sum();
+    let clip = (total as f64 * AUTO_CLIP) as usize;
+    let value_of = |bin: usize| min + (max - min) * bin as f32 / (HISTOGRAM_BINS - 1) as f32;
+
+    let mut seen = 0;
+    let low_bin = histogram
+        .iter()
+        .position(|count| {
+            seen += count;
+            seen > clip
+        })
+        .unwrap_or(0);
+    seen = 0;
+    let high_bin = histogram
+        .iter()
+        .rposition(|count| {
+            seen += count;
+            seen > clip
+        })
+        .unwrap_or(HISTOGRAM_BINS - 1);
+    if low_bin >= high_bin {
+        return [min, max];
+    }
+    [value_of(low_bin), value_of(high_bin)]
+}
@@ -0,0 +1,357 @@
+use crate::config::{bounding_boxes, JsonConfig};
+use crate::export::check_unique_stems;
+use crate::orientation::{image_size, RoiFrame};
+use anyhow::{bail, Context};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const CLASSES_FILE_NAME: &str = "classes.txt";
+
+/// Parses `class cx cy w h` lines with normalized coords.
+/// Class ids are mapped to names through `classes`, unknown ids are kept as numbers.
+pub fn parse_yolo(text: &str, classes: &[String]) -> anyhow::Result<Vec<JsonConfig>> {
+    let mut config = Vec::new();
+    for (line_idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [class, cx, cy, w, h] = fields[..] else {
+            bail!(
+                "line {}: expected 5 fields, found {}",
+                line_idx + 1,
+                fields.len()
+            );
+        };
+        let class_id: usize = class
+            .parse()
+            .with_context(|| format!("line {}: invalid class id {:?}", line_idx + 1, class))?;
+        let [cx, cy, w, h] = [cx, cy, w, h].map(|val| val.parse::<f64>());
+        let (Ok(cx), Ok(cy), Ok(w), Ok(h)) = (cx, cy, w, h) else {
+            bail!("line {}: invalid coordinates", line_idx + 1);
+        };
+        let name = classes
+            .get(class_id)
+            .cloned()
+            .unwrap_or_else(|| class_id.to_string());
+        config.push(JsonConfig {
+            x1: (cx - w / 2.0).clamp(0.0, 1.0),
+            y1: (cy - h / 2.0).clamp(0.0, 1.0),
+            x2: (cx + w / 2.0).clamp(0.0, 1.0),
+            y2: (cy + h / 2.0).clamp(0.0, 1.0),
+            name,
+            ..Default::default()
+        });
+    }
+    Ok(config)
+}
+/// Formats rois as `class cx cy w h` lines, appending unknown names to `classes`.
+pub fn format_yolo(config: &[JsonConfig], classes: &mut Vec<String>) -> String {
+    let mut text = String::new();
+    for roi in config {
+        let class_id = match classes.iter().position(|name| name == roi.class()) {
+            Some(class_id) => class_id,
+            None => {
+                classes.push(roi.class().to_string());
+                classes.len() - 1
+            }
+        };
+        let [cx, cy] = roi.get_center();
+        text += &format!(
+            "{} {:.6} {:.6} {:.6} {:.6}\n",
+            class_id,
+            cx,
+            cy,
+            roi.x2 - roi.x1,
+            roi.y2 - roi.y1
+        );
+    }
+    text
+}
+
+/// Looks for `classes.txt` next to the label file, then one directory up.
+fn find_classes_file(label_path: &Path) -> Option<PathBuf> {
+    let dir = label_path.parent()?;
+    [Some(dir), dir.parent()]
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.join(CLASSES_FILE_NAME))
+        .find(|path| path.is_file())
+}
+pub fn read_classes(path: &Path) -> anyhow::Result<Vec<String>> {
+    let text = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    Ok(text
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+pub fn write_classes(path: &Path, classes: &[String]) -> anyhow::Result<()> {
+    let mut text = classes.join("\n");
+    text.push('\n');
+    fs::write(path, text).with_context(|| format!("writing {}", path.display()))
+}
+
+pub fn read_yolo(path: &Path) -> anyhow::Result<Vec<JsonConfig>> {
+    let classes = match find_classes_file(path) {
+        Some(classes_path) => read_classes(&classes_path)?,
+        None => Vec::new(),
+    };
+    let text = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    parse_yolo(&text, &classes).with_context(|| format!("parsing {}", path.display()))
+}
+/// Writes the label file, adding new class names to the `classes.txt` it uses.
+pub fn write_yolo(path: &Path, config: &[JsonConfig]) -> anyhow::Result<()> {
+    let classes_path =
+        find_classes_file(path).unwrap_or_else(|| path.with_file_name(CLASSES_FILE_NAME));
+    let mut classes = if classes_path.is_file() {
+        read_classes(&classes_path)?
+    } else {
+        Vec::new()
+    };
+    let classes_len = classes.len();
+    let text = format_yolo(config, &mut classes);
+    if classes.len() != classes_len || !classes_path.is_file() {
+        write_classes(&classes_path, &classes)?;
+    }
+    fs::write(path, text).with_context(|| format!("writing {}", path.display()))
+}
+
+/// Sorted names of all rois, the class ids of exports.
+fn sorted_classes(items: &[(PathBuf, Vec<JsonConfig>)]) -> Vec<String> {
+    items
+        .iter()
+        .flat_map(|(_, config)| config.iter().map(|roi| roi.class().to_string()))
+        .collect::<BTreeSet<String>>()
+        .into_iter()
+        .collect()
+}
+
+/// Writes `<image stem>.txt` for every image and a shared `classes.txt` into `out_dir`.
+/// Classes are the sorted roi names, rotated rois are written as their bounding boxes.
+pub fn export_yolo(
+    items: &[(PathBuf, Vec<JsonConfig>)],
+    out_dir: &Path,
+    frame: RoiFrame,
+) -> anyhow::Result<()> {
+    check_unique_stems(items)?;
+    fs::create_dir_all(out_dir).with_context(|| format!("creating {}", out_dir.display()))?;
+    let mut classes = sorted_classes(items);
+
+    for (img_path, config) in items {
+        let Some(stem) = img_path.file_stem() else {
+            continue;
+        };
+        let label_path = out_dir.join(format!("{}.txt", stem.to_string_lossy()));
+        let text = format_yolo(&bounding_boxes(config, img_path, frame)?, &mut classes);
+        fs::write(&label_path, text)
+            .with_context(|| format!("writing {}", label_path.display()))?;
+    }
+    write_classes(&out_dir.join(CLASSES_FILE_NAME), &classes)
+}
+
+/// Formats rois as YOLO-OBB `class x1 y1 x2 y2 x3 y3 x4 y4` lines with normalized corners,
+/// appending unknown names to `classes`.
+pub fn format_yolo_obb(
+    config: &[JsonConfig],
+    img_size: [f64; 2],
+    classes: &mut Vec<String>,
+) -> String {
+    let [width, height] = img_size;
+    let mut text = String::new();
+    for roi in config {
+        let class_id = match classes.iter().position(|name| name == roi.class()) {
+            Some(class_id) => class_id,
+            None => {
+                classes.push(roi.class().to_string());
+                classes.len() - 1
+            }
+        };
+        text += &class_id.to_string();
+        for [x, y] in roi.corners(img_size) {
+            text += &format!(" {:.6} {:.6}", x / width, y / height);
+        }
+        text.push('\n');
+    }
+    text
+}
+/// Writes YOLO-OBB `<image stem>.txt` for every image and a shared `classes.txt` into `out_dir`.
+pub fn export_yolo_obb(
+    items: &[(PathBuf, Vec<JsonConfig>)],
+    out_dir: &Path,
+    frame: RoiFrame,
+) -> anyhow::Result<()> {
+    check_unique_stems(items)?;
+    fs::create_dir_all(out_dir).with_context(|| format!("creating {}", out_dir.display()))?;
+    let mut classes = sorted_classes(items);
+
+    for (img_path, config) in items {
+        let Some(stem) = img_path.file_stem() else {
+            continue;
+        };
+        let [width, height] = image_size(img_path, frame)?;
+        let label_path = out_dir.join(format!("{}.txt", stem.to_string_lossy()));
+        let text = format_yolo_obb(config, [width as f64, height as f64], &mut classes);
+        fs::write(&label_path, text)
+            .with_context(|| format!("writing {}", label_path.display()))?;
+    }
+    write_classes(&out_dir.join(CLASSES_FILE_NAME), &classes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{test_dir, write_png};
+
+    /// 1x1 pixel square turned by 45 degrees in the middle of a 2x4 image.
+    fn turned_square() -> JsonConfig {
+        JsonConfig {
+            x1: 0.25,
+            y1: 0.375,
+            x2: 0.75,
+            y2: 0.625,
+            name: String::from("cat"),
+            angle: 45.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn export_boxes_rotated_rois_in_the_oriented_size() {
+        let dir = test_dir("yolo_oriented");
+        let img_path = dir.join("img.png");
+        write_png(&img_path, [4, 2], 6);
+        export_yolo(
+            &[(img_path, vec![turned_square()])],
+            &dir,
+            RoiFrame::Oriented,
+        )
+        .unwrap();
+
+        let config = read_yolo(&dir.join("img.txt")).unwrap();
+        let diagonal = 2f64.sqrt();
+        assert!((config[0].x2 - config[0].x1 - diagonal / 2.0).abs() < 1e-5);
+        assert!((config[0].y2 - config[0].y1 - diagonal / 4.0).abs() < 1e-5);
+        assert_eq!(config[0].name, "cat");
+    }
+
+    #[test]
+    fn parse_and_format_round_trip() {
+        let mut classes = vec![String::from("dog")];
+        let config = vec![
+            JsonConfig {
+                x1: 0.1,
+                y1: 0.2,
+                x2: 0.5,
+                y2: 0.8,
+                name: String::from("cat"),
+                ..Default::default()
+            },
+            JsonConfig {
+                x2: 1.0,
+                y2: 0.5,
+                name: String::from("dog"),
+                ..Default::default()
+            },
+        ];
+        let text = format_yolo(&config, &mut classes);
+        assert_eq!(classes, ["dog", "cat"]);
+        assert_eq!(
+            text,
+            "1 0.300000 0.500000 0.400000 0.600000\n0 0.500000 0.250000 1.000000 0.500000\n"
+        );
+
+        let parsed = parse_yolo(&text, &classes).unwrap();
+        for (parsed, roi) in parsed.iter().zip(&config) {
+            assert_eq!(parsed.name, roi.name);
+            for (a, b) in [
+                (parsed.x1, roi.x1),
+                (parsed.y1, roi.y1),
+                (parsed.x2, roi.x2),
+                (parsed.y2, roi.y2),
+            ] {
+                assert!((a - b).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_keeps_unknown_class_ids_and_rejects_bad_lines() {
+        let config = parse_yolo("\n3 0.5 0.5 0.2 0.2\n", &[]).unwrap();
+        assert_eq!(config.len(), 1);
+        assert_eq!(config[0].name, "3");
+
+        let Err(err) = parse_yolo("0 0.5 0.5 0.2\n", &[]) else {
+            panic!("expected an error for a short line");
+        };
+        assert_eq!(err.to_string(), "line 1: expected 5 fields, found 4");
+        assert!(parse_yolo("0 0.5 0.5 0.2 0.2\ncat 0.5 0.5 0.2 0.2", &[]).is_err());
+        assert!(parse_yolo("0 0.5 x 0.2 0.2", &[]).is_err());
+    }
+
+    #[test]
+    fn write_and_read_share_the_classes_file() {
+        let dir = test_dir("yolo_classes");
+        let config = vec![JsonConfig {
+            x2: 0.5,
+            y2: 0.5,
+            name: String::from("cat"),
+            ..Default::default()
+        }];
+        write_yolo(&dir.join("a.txt"), &config).unwrap();
+        let dog = vec![JsonConfig {
+            name: String::from("dog"),
+            ..config[0].clone()
+        }];
+        write_yolo(&dir.join("b.txt"), &dog).unwrap();
+
+        let classes = read_classes(&dir.join(CLASSES_FILE_NAME)).unwrap();
+        assert_eq!(classes, ["cat", "dog"]);
+        assert_eq!(read_yolo(&dir.join("b.txt")).unwrap()[0].name, "dog");
+    }
+
+    #[test]
+    fn obb_lines_list_the_turned_corners() {
+        let mut classes = Vec::new();
+        let text = format_yolo_obb(&[turned_square()], [2.0, 4.0], &mut classes);
+        let fields: Vec<f64> = text
+            .split_whitespace()
+            .map(|f| f.parse().unwrap())
+            .collect();
+        assert_eq!(fields.len(), 9);
+        assert_eq!(classes, ["cat"]);
+        let half_diagonal = 2f64.sqrt() / 2.0;
+        let mut xs: Vec<f64> = fields[1..].iter().step_by(2).map(|x| x * 2.0).collect();
+        let mut ys: Vec<f64> = fields[2..].iter().step_by(2).map(|y| y * 4.0).collect();
+        xs.sort_by(f64::total_cmp);
+        ys.sort_by(f64::total_cmp);
+        // a diamond: one corner on each side of the center
+        for (sorted, center) in [(xs, 1.0), (ys, 2.0)] {
+            assert!((sorted[0] - (center - half_diagonal)).abs() < 1e-5);
+            assert!((sorted[1] - center).abs() < 1e-5);
+            assert!((sorted[2] - center).abs() < 1e-5);
+            assert!((sorted[3] - (center + half_diagonal)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn export_refuses_images_sharing_a_stem() {
+        let dir = test_dir("yolo_same_stem");
+        let items: Vec<(PathBuf, Vec<JsonConfig>)> = ["a", "b"]
+            .iter()
+            .map(|sub_dir| {
+                fs::create_dir_all(dir.join(sub_dir)).unwrap();
+                let img_path = dir.join(sub_dir).join("001.png");
+                write_png(&img_path, [4, 2], 1);
+                (img_path, Vec::new())
+            })
+            .collect();
+        let out_dir = dir.join("labels");
+        assert!(export_yolo(&items, &out_dir, RoiFrame::Oriented).is_err());
+        assert!(!out_dir.join("001.txt").exists());
+        export_yolo(&items[..1], &out_dir, RoiFrame::Oriented).unwrap();
+        assert!(out_dir.join("001.txt").exists());
+    }
+}